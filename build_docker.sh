@@ -30,5 +30,7 @@ perl -i -pe 's/\["cdylib"\]/\["cdylib", "rlib"\]/' Cargo.toml
 
 mkdir -p res
 cp $DIR/target/wasm32-unknown-unknown/release/test_oracle.wasm $DIR/res/
+cp $DIR/target/wasm32-unknown-unknown/release/test_dex.wasm $DIR/res/
+cp $DIR/target/wasm32-unknown-unknown/release/test_price_feed.wasm $DIR/res/
 cp $DIR/target/wasm32-unknown-unknown/release/contract.wasm $DIR/res/burrowland.wasm
 
@@ -27,11 +27,43 @@ pub struct PriceData {
     pub prices: Vec<AssetOptionalPrice>,
 }
 
+/// The price reported by a pull oracle price feed together with its confidence interval.
+/// The price is expected to be within `multiplier ± confidence`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeedPrice {
+    pub price: Price,
+    /// The confidence interval in the same decimals as the price multiplier.
+    #[serde(with = "u128_dec_format")]
+    pub confidence: Balance,
+    /// The block timestamp when the price was published.
+    #[serde(with = "u64_dec_format")]
+    pub timestamp: Timestamp,
+}
+
 pub trait OraclePriceReceiver {
     fn oracle_on_call(&mut self, sender_id: AccountId, data: PriceData, msg: String);
 }
 
+/// The message attached to `ft_transfer_call` of the input token to the DEX contract.
+/// The DEX should swap the received amount into `token_out` and transfer the result back to the
+/// sender using `ft_transfer_call` with the given `msg`. If the swap can't be done, the DEX should
+/// return the input amount as unused.
+/// The output transfer should be sent within the promise chain of `ft_on_transfer`, so it's
+/// received before the input transfer is resolved. Otherwise the swap stays pending until the
+/// output is received or the swap is cancelled by the guardian.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapMsg {
+    pub token_out: AccountId,
+    #[serde(with = "u128_dec_format")]
+    pub min_amount_out: Balance,
+    pub msg: String,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
 #[serde(crate = "near_sdk::serde")]
 pub struct Price {
     #[serde(with = "u128_dec_format")]
@@ -87,6 +119,30 @@ pub mod u64_dec_format {
     }
 }
 
+pub mod option_u64_dec_format {
+    use near_sdk::serde::de;
+    use near_sdk::serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(num: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match num {
+            Some(num) => serializer.serialize_str(&num.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| s.parse().map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
 pub fn to_nano(ts: u32) -> Timestamp {
     Timestamp::from(ts) * 10u64.pow(9)
 }
@@ -27,12 +27,35 @@ pub struct Account {
 
     /// Staking of booster token.
     pub booster_staking: Option<BoosterStaking>,
+
+    /// The swap through the DEX that is waiting for the output tokens.
+    pub pending_swap: Option<PendingSwap>,
+
+    /// Conditional orders that can be executed by keepers.
+    pub orders: Vec<AccountOrder>,
+    /// The ID of the next order placed by the account.
+    pub next_order_id: OrderId,
+
+    /// Whether anyone can repay the borrowed balance of the account using its supplied balance
+    /// of the same token.
+    pub auto_repay_enabled: bool,
+
+    /// The lifetime amounts of claimed farm rewards per farm and reward token.
+    #[serde(skip_serializing)]
+    pub claimed_farm_rewards: HashMap<FarmId, HashMap<TokenId, Balance>>,
+
+    /// When the voting ends on all proposals the account voted on in nanoseconds. The staked
+    /// booster token can't be unstaked until then, so the same stake can't vote twice.
+    #[serde(with = "u64_dec_format")]
+    pub booster_vote_lock_timestamp: Timestamp,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum VAccount {
     V0(AccountV0),
     V1(AccountV1),
+    V2(AccountV2),
     Current(Account),
 }
 
@@ -41,6 +64,7 @@ impl VAccount {
         match self {
             VAccount::V0(c) => c.into_account(is_view),
             VAccount::V1(c) => c.into_account(is_view),
+            VAccount::V2(c) => c.into(),
             VAccount::Current(c) => c,
         }
     }
@@ -63,10 +87,17 @@ impl Account {
             affected_farms: HashSet::new(),
             storage_tracker: Default::default(),
             booster_staking: None,
+            pending_swap: None,
+            orders: vec![],
+            next_order_id: 0,
+            auto_repay_enabled: false,
+            claimed_farm_rewards: HashMap::new(),
+            booster_vote_lock_timestamp: 0,
         }
     }
 
     pub fn increase_collateral(&mut self, token_id: &TokenId, shares: Shares) {
+        self.add_affected_farm(FarmId::Collateral(token_id.clone()));
         self.collateral
             .entry(token_id.clone())
             .or_insert_with(|| 0.into())
@@ -74,6 +105,7 @@ impl Account {
     }
 
     pub fn decrease_collateral(&mut self, token_id: &TokenId, shares: Shares) {
+        self.add_affected_farm(FarmId::Collateral(token_id.clone()));
         let current_collateral = self.internal_unwrap_collateral(token_id);
         if let Some(new_balance) = current_collateral.0.checked_sub(shares.0) {
             if new_balance > 0 {
@@ -132,7 +164,11 @@ impl Account {
         potential_farms.insert(FarmId::NetTvl);
         potential_farms.extend(self.supplied.keys().cloned().map(FarmId::Supplied));
         potential_farms.extend(self.collateral.keys().cloned().map(FarmId::Supplied));
+        potential_farms.extend(self.collateral.keys().cloned().map(FarmId::Collateral));
         potential_farms.extend(self.borrowed.keys().cloned().map(FarmId::Borrowed));
+        if self.booster_staking.is_some() {
+            potential_farms.insert(FarmId::BoosterStaking);
+        }
         potential_farms
     }
 
@@ -145,6 +181,31 @@ impl Account {
         (supplied_shares + collateral_shares).into()
     }
 
+    /// Adds the claimed farm reward to the lifetime totals and returns the new total amount
+    /// claimed from the given farm in the given reward token.
+    pub fn add_claimed_farm_reward(
+        &mut self,
+        farm_id: &FarmId,
+        token_id: &TokenId,
+        amount: Balance,
+    ) -> Balance {
+        let claimed_amount = self
+            .claimed_farm_rewards
+            .entry(farm_id.clone())
+            .or_default()
+            .entry(token_id.clone())
+            .or_default();
+        *claimed_amount += amount;
+        *claimed_amount
+    }
+
+    pub fn get_collateral_shares(&self, token_id: &TokenId) -> Shares {
+        self.collateral
+            .get(token_id)
+            .cloned()
+            .unwrap_or_else(|| 0.into())
+    }
+
     pub fn get_borrowed_shares(&self, token_id: &TokenId) -> Shares {
         self.borrowed
             .get(&token_id)
@@ -20,6 +20,21 @@ pub enum FarmId {
     Supplied(TokenId),
     Borrowed(TokenId),
     NetTvl,
+    /// Farms only the collateral shares of the given token.
+    Collateral(TokenId),
+    /// Farms the xBooster amount of the booster token stakers.
+    BoosterStaking,
+}
+
+impl FarmId {
+    /// Returns the xBooster amount that boosts the shares of the farm. The shares of the booster
+    /// staking farm are already the xBooster amount, so they are not boosted again.
+    pub fn boosting_x_booster_amount(&self, x_booster_amount: Balance) -> Balance {
+        match self {
+            FarmId::BoosterStaking => 0,
+            _ => x_booster_amount,
+        }
+    }
 }
 
 /// A data required to keep track of a farm for an account.
@@ -139,9 +154,22 @@ impl Contract {
     }
 
     pub fn internal_account_apply_affected_farms(&mut self, account: &mut Account) {
+        self.internal_account_claim_affected_farms(account, false);
+    }
+
+    /// Claims the rewards of the affected farms and recomputes the boosted shares of the account.
+    /// The claimed rewards are deposited to the supplied balance of the account, unless
+    /// `claim_to_wallet` is set. In this case the rewards of the assets that can be withdrawn are
+    /// returned instead to be transferred to the account.
+    pub fn internal_account_claim_affected_farms(
+        &mut self,
+        account: &mut Account,
+        claim_to_wallet: bool,
+    ) -> HashMap<TokenId, Balance> {
         let config = self.internal_config();
+        let mut wallet_rewards: HashMap<TokenId, Balance> = HashMap::new();
         if account.affected_farms.is_empty() {
-            return;
+            return wallet_rewards;
         }
         account.add_affected_farm(FarmId::NetTvl);
         let mut all_rewards: HashMap<TokenId, Balance> = HashMap::new();
@@ -152,6 +180,20 @@ impl Contract {
                 let (account_farm, new_rewards, inactive_rewards) =
                     self.internal_account_farm_claim(account, &farm_id, &asset_farm);
                 for (token_id, amount) in new_rewards {
+                    let total_claimed_amount =
+                        account.add_claimed_farm_reward(&farm_id, &token_id, amount);
+                    events::emit::farm_reward_claimed(
+                        &account.account_id,
+                        &farm_id,
+                        &token_id,
+                        amount,
+                        total_claimed_amount,
+                    );
+                    if claim_to_wallet && self.internal_unwrap_asset(&token_id).config.can_withdraw
+                    {
+                        *wallet_rewards.entry(token_id).or_default() += amount;
+                        continue;
+                    }
                     let new_farm_id = FarmId::Supplied(token_id.clone());
                     *all_rewards.entry(token_id).or_default() += amount;
                     if account.add_affected_farm(new_farm_id.clone()) {
@@ -167,7 +209,7 @@ impl Contract {
         let booster_balance = account
             .booster_staking
             .as_ref()
-            .map(|b| b.x_booster_amount)
+            .map(|b| b.get_x_booster_amount(&config, env::block_timestamp()))
             .unwrap_or(0);
         let booster_base = 10u128.pow(config.booster_decimals as u32);
 
@@ -175,6 +217,8 @@ impl Contract {
             let shares = match &farm_id {
                 FarmId::Supplied(token_id) => account.get_supplied_shares(token_id).0,
                 FarmId::Borrowed(token_id) => account.get_borrowed_shares(token_id).0,
+                FarmId::Collateral(token_id) => account.get_collateral_shares(token_id).0,
+                FarmId::BoosterStaking => booster_balance,
                 FarmId::NetTvl => {
                     let mut total_supplied: BigDecimal = BigDecimal::zero();
                     let mut total_borrowed: BigDecimal = BigDecimal::zero();
@@ -222,18 +266,13 @@ impl Contract {
                 let account_farm_reward = account_farm.rewards.get_mut(token_id).unwrap();
                 asset_farm_reward.boosted_shares -= account_farm_reward.boosted_shares;
                 if shares > 0 {
-                    let extra_shares = if asset_farm_reward.booster_log_base > 0
-                        && booster_balance > booster_base
-                    {
-                        let log_base =
-                            (asset_farm_reward.booster_log_base as f64) / (booster_base as f64);
-                        ((shares as f64)
-                            * ((booster_balance as f64) / (booster_base as f64)).log(log_base))
-                            as u128
-                    } else {
-                        0
-                    };
-                    account_farm_reward.boosted_shares = shares + extra_shares;
+                    account_farm_reward.boosted_shares =
+                        asset_farm_reward.boost_curve.compute_boosted_shares(
+                            shares,
+                            farm_id.boosting_x_booster_amount(booster_balance),
+                            asset_farm_reward.booster_log_base,
+                            booster_base,
+                        );
                     asset_farm_reward.boosted_shares += account_farm_reward.boosted_shares;
                 }
             }
@@ -251,6 +290,7 @@ impl Contract {
                 account.farms.remove(&farm_id);
             }
         }
+        wallet_rewards
     }
 }
 
@@ -259,13 +299,80 @@ impl Contract {
     /// Claims all unclaimed farm rewards and starts farming new farms.
     /// If the account_id is given, then it claims farms for the given account_id or uses
     /// predecessor_account_id otherwise.
-    pub fn account_farm_claim_all(&mut self, account_id: Option<AccountId>) {
+    /// If `claim_to_wallet` is true, then the rewards are transferred to the account instead of
+    /// being deposited to its supplied balance. If a transfer fails, the reward is deposited to
+    /// the supplied balance. The rewards of the assets that can't be withdrawn are always
+    /// deposited.
+    /// - Panics if `claim_to_wallet` is true and the account_id is not the predecessor.
+    /// - Requires one yoctoNEAR if `claim_to_wallet` is true.
+    #[payable]
+    pub fn account_farm_claim_all(
+        &mut self,
+        account_id: Option<AccountId>,
+        claim_to_wallet: Option<bool>,
+    ) {
         let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let claim_to_wallet = claim_to_wallet.unwrap_or(false);
+        if claim_to_wallet {
+            assert_one_yocto();
+        }
+        assert!(
+            !claim_to_wallet || account_id == env::predecessor_account_id(),
+            "Only the account owner can claim rewards to the wallet"
+        );
         let mut account = self.internal_unwrap_account(&account_id);
         account
             .affected_farms
             .extend(account.get_all_potential_farms());
+        let wallet_rewards =
+            self.internal_account_claim_affected_farms(&mut account, claim_to_wallet);
+        self.internal_set_account(&account_id, account);
+        for (token_id, amount) in wallet_rewards {
+            self.internal_ft_transfer(&account_id, &token_id, amount);
+            events::emit::withdraw_started(&account_id, amount, &token_id);
+        }
+    }
+
+    /// Recomputes the boosted shares of all farms of the given account using its current
+    /// effective xBooster amount. Can be called by anyone to refresh the boosted shares that became
+    /// stale due to the xBooster decay. The unclaimed rewards of these farms are claimed to the
+    /// supplied balance of the account.
+    /// - Panics if the account is not registered.
+    pub fn account_farm_poke(&mut self, account_id: AccountId) {
+        let mut account = self.internal_unwrap_account(&account_id);
+        account.affected_farms.extend(account.farms.keys().cloned());
         self.internal_account_apply_affected_farms(&mut account);
         self.internal_set_account(&account_id, account);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOSTER_BASE: Balance = 10u128.pow(18);
+
+    #[test]
+    fn test_booster_staking_shares_are_not_boosted() {
+        let shares = BOOSTER_BASE * 100;
+        let boost_curves = [
+            BoostCurve::Logarithmic,
+            BoostCurve::CappedLinear {
+                max_multiplier: MAX_RATIO * 3,
+                full_boost_x_booster_amount: shares,
+            },
+        ];
+        for boost_curve in boost_curves.iter() {
+            let compute_boosted_shares = |farm_id: FarmId| {
+                boost_curve.compute_boosted_shares(
+                    shares,
+                    farm_id.boosting_x_booster_amount(shares),
+                    BOOSTER_BASE * 10,
+                    BOOSTER_BASE,
+                )
+            };
+            assert_eq!(compute_boosted_shares(FarmId::BoosterStaking), shares);
+            assert_eq!(compute_boosted_shares(FarmId::NetTvl), shares * 3);
+        }
+    }
+}
@@ -30,6 +30,14 @@ pub struct AccountDetailedView {
     pub has_non_farmed_assets: bool,
     /// Staking of booster token.
     pub booster_staking: Option<BoosterStaking>,
+    /// The swap through the DEX that is waiting for the output tokens.
+    pub pending_swap: Option<PendingSwap>,
+    /// Conditional orders that can be executed by keepers.
+    pub orders: Vec<AccountOrder>,
+    /// Whether anyone can repay the borrowed balance using the supplied balance of the same token.
+    pub auto_repay_enabled: bool,
+    /// The lifetime amounts of claimed farm rewards.
+    pub claimed_farm_rewards: Vec<ClaimedFarmRewardView>,
 }
 
 #[derive(Serialize)]
@@ -40,6 +48,16 @@ pub struct AccountFarmView {
     pub rewards: Vec<AccountFarmRewardView>,
 }
 
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimedFarmRewardView {
+    pub farm_id: FarmId,
+    pub reward_token_id: TokenId,
+    #[serde(with = "u128_dec_format")]
+    pub claimed_amount: Balance,
+}
+
 #[derive(Serialize)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
 #[serde(crate = "near_sdk::serde")]
@@ -50,11 +68,20 @@ pub struct AccountFarmRewardView {
     pub boosted_shares: Balance,
     #[serde(with = "u128_dec_format")]
     pub unclaimed_amount: Balance,
+    /// The expected multiplier of the shares based on the current xBooster amount.
+    pub boost_multiplier: BigDecimal,
 }
 
 impl Contract {
     pub fn account_into_detailed_view(&self, account: Account) -> AccountDetailedView {
         let mut potential_farms = account.get_all_potential_farms();
+        let config = self.internal_config();
+        let booster_balance = account
+            .booster_staking
+            .as_ref()
+            .map(|b| b.get_x_booster_amount(&config, env::block_timestamp()))
+            .unwrap_or(0);
+        let booster_base = 10u128.pow(config.booster_decimals as u32);
         let farms = account
             .farms
             .keys()
@@ -63,6 +90,7 @@ impl Contract {
                 // Remove already active farm.
                 potential_farms.remove(&farm_id);
                 let mut asset_farm = self.internal_unwrap_asset_farm(&farm_id, true);
+                let boosting_x_booster_amount = farm_id.boosting_x_booster_amount(booster_balance);
                 let (account_farm, new_rewards, inactive_rewards) =
                     self.internal_account_farm_claim(&account, &farm_id, &asset_farm);
                 AccountFarmView {
@@ -88,11 +116,18 @@ impl Contract {
                                 .find(|(token_id, _)| token_id == &reward_token_id)
                                 .map(|(_, amount)| *amount)
                                 .unwrap_or(0);
+                            let boost_multiplier =
+                                asset_farm_reward.boost_curve.compute_multiplier(
+                                    boosting_x_booster_amount,
+                                    asset_farm_reward.booster_log_base,
+                                    booster_base,
+                                );
                             AccountFarmRewardView {
                                 reward_token_id,
                                 asset_farm_reward,
                                 boosted_shares,
                                 unclaimed_amount,
+                                boost_multiplier,
                             }
                         })
                         .collect(),
@@ -123,6 +158,24 @@ impl Contract {
             farms,
             has_non_farmed_assets,
             booster_staking: account.booster_staking,
+            pending_swap: account.pending_swap,
+            orders: account.orders,
+            auto_repay_enabled: account.auto_repay_enabled,
+            claimed_farm_rewards: account
+                .claimed_farm_rewards
+                .into_iter()
+                .flat_map(|(farm_id, rewards)| {
+                    rewards
+                        .into_iter()
+                        .map(
+                            move |(reward_token_id, claimed_amount)| ClaimedFarmRewardView {
+                                farm_id: farm_id.clone(),
+                                reward_token_id,
+                                claimed_amount,
+                            },
+                        )
+                })
+                .collect(),
         }
     }
 
@@ -32,6 +32,32 @@ pub enum Action {
     ForceClose {
         account_id: AccountId,
     },
+    /// Swaps the collateral into another token through the DEX. The output token is added to the
+    /// collateral once it's received from the DEX. Until then, the `min_amount_out` of the output
+    /// token is counted towards the collateral.
+    SwapCollateral {
+        token_in: AssetAmount,
+        token_out: TokenId,
+        min_amount_out: U128,
+    },
+    /// Borrows the input token and swaps it through the DEX into the output token to repay the
+    /// borrowed output token. Until the output is received from the DEX, the `min_amount_out` of
+    /// the output token is counted as repaid.
+    SwapDebt {
+        token_in: AssetAmount,
+        token_out: TokenId,
+        min_amount_out: U128,
+    },
+    /// Converts the collateral into the repayment of the borrowed output token without the
+    /// liquidation discount. If `min_amount_out` is given, the collateral is swapped through the
+    /// DEX. Otherwise the reserves are used at the oracle prices minus the reserve deleverage fee.
+    /// The health factor of the account can't decrease, so the account that is already at risk
+    /// can deleverage as well.
+    Deleverage {
+        token_in: AssetAmount,
+        token_out: TokenId,
+        min_amount_out: Option<U128>,
+    },
 }
 
 impl Contract {
@@ -75,6 +101,8 @@ impl Contract {
                     need_risk_check = true;
                     account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
                     account.add_affected_farm(FarmId::Borrowed(asset_amount.token_id.clone()));
+                    prices.assert_price_stable(&asset_amount.token_id);
+                    prices.assert_account_prices_stable(account);
                     let amount = self.internal_borrow(account, &asset_amount);
                     events::emit::borrow(&account_id, amount, &asset_amount.token_id);
                 }
@@ -114,6 +142,93 @@ impl Contract {
                     );
                     self.internal_force_close(&prices, &liquidation_account_id);
                 }
+                Action::SwapCollateral {
+                    token_in,
+                    token_out,
+                    min_amount_out,
+                } => {
+                    need_number_check = true;
+                    need_risk_check = true;
+                    let amount = self.internal_swap_collateral(
+                        account_id,
+                        account,
+                        &token_in,
+                        &token_out,
+                        min_amount_out.0,
+                    );
+                    events::emit::swap_collateral(
+                        account_id,
+                        amount,
+                        &token_in.token_id,
+                        min_amount_out.0,
+                        &token_out,
+                    );
+                }
+                Action::SwapDebt {
+                    token_in,
+                    token_out,
+                    min_amount_out,
+                } => {
+                    need_number_check = true;
+                    need_risk_check = true;
+                    prices.assert_price_stable(&token_in.token_id);
+                    prices.assert_account_prices_stable(account);
+                    let amount = self.internal_swap_debt(
+                        account_id,
+                        account,
+                        &token_in,
+                        &token_out,
+                        min_amount_out.0,
+                    );
+                    events::emit::swap_debt(
+                        account_id,
+                        amount,
+                        &token_in.token_id,
+                        min_amount_out.0,
+                        &token_out,
+                    );
+                }
+                Action::Deleverage {
+                    token_in,
+                    token_out,
+                    min_amount_out,
+                } => {
+                    need_number_check = true;
+                    let max_discount = self.compute_max_discount(account, &prices);
+                    if let Some(min_amount_out) = min_amount_out {
+                        let amount = self.internal_swap_deleverage(
+                            account_id,
+                            account,
+                            &token_in,
+                            &token_out,
+                            min_amount_out.0,
+                        );
+                        events::emit::swap_deleverage(
+                            account_id,
+                            amount,
+                            &token_in.token_id,
+                            min_amount_out.0,
+                            &token_out,
+                        );
+                    } else {
+                        let (amount, amount_out) = self.internal_deleverage_with_reserves(
+                            account, &prices, &token_in, &token_out,
+                        );
+                        events::emit::deleverage(
+                            account_id,
+                            amount,
+                            &token_in.token_id,
+                            amount_out,
+                            &token_out,
+                        );
+                    }
+                    let new_max_discount = self.compute_max_discount(account, &prices);
+                    assert!(
+                        new_max_discount == BigDecimal::zero() || new_max_discount < max_discount,
+                        "The health factor of the account can't decrease. New discount {} >= old discount {}",
+                        new_max_discount, max_discount
+                    );
+                }
             }
         }
         if need_number_check {
@@ -123,7 +238,7 @@ impl Contract {
             );
         }
         if need_risk_check {
-            assert!(self.compute_max_discount(account, &prices) == BigDecimal::zero());
+            self.assert_account_healthy(account, &prices);
         }
 
         self.internal_account_apply_affected_farms(account);
@@ -149,6 +264,26 @@ impl Contract {
         shares
     }
 
+    /// Deposits the given amount into the supplied pool and adds the shares to the collateral.
+    pub fn internal_deposit_collateral(
+        &mut self,
+        account: &mut Account,
+        token_id: &TokenId,
+        amount: Balance,
+    ) -> Shares {
+        let mut asset = self.internal_unwrap_asset(token_id);
+
+        let shares: Shares = asset.supplied.amount_to_shares(amount, false);
+
+        account.increase_collateral(token_id, shares);
+        account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+
+        asset.supplied.deposit(shares, amount);
+        self.internal_set_asset(token_id, asset);
+
+        shares
+    }
+
     pub fn internal_withdraw(
         &mut self,
         account: &mut Account,
@@ -232,31 +367,15 @@ impl Contract {
         asset_amount: &AssetAmount,
     ) -> Balance {
         let mut asset = self.internal_unwrap_asset(&asset_amount.token_id);
-        assert!(asset.config.can_borrow, "Thi asset can't be used borrowed");
-
         let mut account_asset = account.internal_get_asset_or_default(&asset_amount.token_id);
 
-        let available_amount = asset.available_amount();
-        let max_borrow_shares = asset.borrowed.amount_to_shares(available_amount, false);
-
-        let (borrowed_shares, amount) =
-            asset_amount_to_shares(&asset.borrowed, max_borrow_shares, &asset_amount, true);
-
-        assert!(
-            amount <= available_amount,
-            "Borrow error: Exceeded available amount {} of {}",
-            available_amount,
-            &asset_amount.token_id
-        );
+        let amount = borrow_asset_amount(&mut asset, account, asset_amount);
 
         let supplied_shares: Shares = asset.supplied.amount_to_shares(amount, false);
 
-        asset.borrowed.deposit(borrowed_shares, amount);
         asset.supplied.deposit(supplied_shares, amount);
         self.internal_set_asset(&asset_amount.token_id, asset);
 
-        account.increase_borrowed(&asset_amount.token_id, borrowed_shares);
-
         account_asset.deposit_shares(supplied_shares);
         account.internal_set_asset(&asset_amount.token_id, account_asset);
 
@@ -314,6 +433,7 @@ impl Contract {
         out_assets: Vec<AssetAmount>,
     ) {
         let mut liquidation_account = self.internal_unwrap_account(liquidation_account_id);
+        prices.assert_account_prices_stable(&liquidation_account);
 
         let max_discount = self.compute_max_discount(&liquidation_account, &prices);
         assert!(
@@ -397,6 +517,7 @@ impl Contract {
         );
 
         let mut liquidation_account = self.internal_unwrap_account(liquidation_account_id);
+        prices.assert_account_prices_stable(&liquidation_account);
 
         let mut borrowed_sum = BigDecimal::zero();
         let mut collateral_sum = BigDecimal::zero();
@@ -416,7 +537,8 @@ impl Contract {
                     asset.config.extra_decimals,
                 );
             self.internal_set_asset(&token_id, asset);
-            affected_farms.push(FarmId::Supplied(token_id));
+            affected_farms.push(FarmId::Supplied(token_id.clone()));
+            affected_farms.push(FarmId::Collateral(token_id));
         }
 
         for (token_id, shares) in liquidation_account.borrowed.drain() {
@@ -459,20 +581,88 @@ impl Contract {
             return BigDecimal::zero();
         }
 
+        let (collateral_sum, borrowed_sum) = self.compute_adjusted_sums(account, prices, false);
+
+        if borrowed_sum <= collateral_sum {
+            BigDecimal::zero()
+        } else {
+            (borrowed_sum - collateral_sum) / borrowed_sum / BigDecimal::from(2u32)
+        }
+    }
+
+    /// Returns the ratio of the adjusted collateral sum to the adjusted borrowed sum.
+    /// The account is at risk when the health factor is below 1. Returns `None` if the account
+    /// has no borrowed value.
+    pub fn compute_health_factor(&self, account: &Account, prices: &Prices) -> Option<BigDecimal> {
+        if account.borrowed.is_empty() {
+            return None;
+        }
+
+        let (collateral_sum, borrowed_sum) = self.compute_adjusted_sums(account, prices, false);
+        if borrowed_sum == BigDecimal::zero() {
+            None
+        } else {
+            Some(collateral_sum / borrowed_sum)
+        }
+    }
+
+    /// Panics if the adjusted borrowed sum of the account is larger than the adjusted collateral
+    /// sum. The collateral without a price is valued at zero, so the account can still decrease
+    /// the collateral or borrow when the oracle doesn't provide some of the collateral prices, as
+    /// long as the rest of the collateral covers the borrowed sum. The prices of all borrowed
+    /// assets are required.
+    pub fn assert_account_healthy(&self, account: &Account, prices: &Prices) {
+        if account.borrowed.is_empty() {
+            return;
+        }
+
+        let (collateral_sum, borrowed_sum) = self.compute_adjusted_sums(account, prices, true);
+        assert!(
+            borrowed_sum <= collateral_sum,
+            "The adjusted borrowed sum {} exceeds the adjusted collateral sum {}",
+            borrowed_sum,
+            collateral_sum
+        );
+    }
+
+    /// Returns the sums of the collateral and the borrowed values adjusted by the volatility
+    /// ratios of the assets. The assets are valued using the prices of their price modes.
+    /// If `skip_missing_collateral_prices` is true, then the collateral without a price is valued
+    /// at zero instead of panicking.
+    fn compute_adjusted_sums(
+        &self,
+        account: &Account,
+        prices: &Prices,
+        skip_missing_collateral_prices: bool,
+    ) -> (BigDecimal, BigDecimal) {
         let collateral_sum =
             account
                 .collateral
                 .iter()
                 .fold(BigDecimal::zero(), |sum, (token_id, shares)| {
+                    if skip_missing_collateral_prices && prices.get(token_id).is_none() {
+                        return sum;
+                    }
                     let asset = self.internal_unwrap_asset(&token_id);
                     let balance = asset.supplied.shares_to_amount(*shares, false);
+                    let price = self.get_valuation_price(
+                        token_id,
+                        prices,
+                        asset.config.collateral_price_mode,
+                        true,
+                    );
                     sum + BigDecimal::from_balance_price(
                         balance,
-                        prices.get_unwrap(&token_id),
+                        &price,
                         asset.config.extra_decimals,
                     )
                     .mul_ratio(asset.config.volatility_ratio)
-                });
+                })
+                + self.compute_pending_swap_collateral(
+                    account,
+                    prices,
+                    skip_missing_collateral_prices,
+                );
 
         let borrowed_sum =
             account
@@ -480,24 +670,57 @@ impl Contract {
                 .iter()
                 .fold(BigDecimal::zero(), |sum, (token_id, shares)| {
                     let asset = self.internal_unwrap_asset(&token_id);
-                    let balance = asset.borrowed.shares_to_amount(*shares, true);
+                    let balance = asset
+                        .borrowed
+                        .shares_to_amount(*shares, true)
+                        .saturating_sub(account.get_pending_swap_repayment(token_id));
+                    let price = self.get_valuation_price(
+                        token_id,
+                        prices,
+                        asset.config.debt_price_mode,
+                        false,
+                    );
                     sum + BigDecimal::from_balance_price(
                         balance,
-                        prices.get_unwrap(&token_id),
+                        &price,
                         asset.config.extra_decimals,
                     )
                     .div_ratio(asset.config.volatility_ratio)
                 });
 
-        if borrowed_sum <= collateral_sum {
-            BigDecimal::zero()
-        } else {
-            (borrowed_sum - collateral_sum) / borrowed_sum / BigDecimal::from(2u32)
-        }
+        (collateral_sum, borrowed_sum)
     }
 }
 
-fn asset_amount_to_shares(
+/// Increases the borrowed balance of the asset and the account by the given asset amount and
+/// returns the borrowed amount. The caller is responsible for the borrowed tokens.
+pub(crate) fn borrow_asset_amount(
+    asset: &mut Asset,
+    account: &mut Account,
+    asset_amount: &AssetAmount,
+) -> Balance {
+    assert!(asset.config.can_borrow, "This asset can't be borrowed");
+
+    let available_amount = asset.available_amount();
+    let max_borrow_shares = asset.borrowed.amount_to_shares(available_amount, false);
+
+    let (borrowed_shares, amount) =
+        asset_amount_to_shares(&asset.borrowed, max_borrow_shares, asset_amount, true);
+
+    assert!(
+        amount <= available_amount,
+        "Borrow error: Exceeded available amount {} of {}",
+        available_amount,
+        &asset_amount.token_id
+    );
+
+    asset.borrowed.deposit(borrowed_shares, amount);
+    account.increase_borrowed(&asset_amount.token_id, borrowed_shares);
+
+    amount
+}
+
+pub(crate) fn asset_amount_to_shares(
     pool: &Pool,
     available_shares: Shares,
     asset_amount: &AssetAmount,
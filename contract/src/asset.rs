@@ -27,6 +27,7 @@ pub struct Asset {
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VAsset {
     V0(AssetV0),
+    V1(AssetV1),
     Current(Asset),
 }
 
@@ -34,6 +35,7 @@ impl From<VAsset> for Asset {
     fn from(v: VAsset) -> Self {
         match v {
             VAsset::V0(v) => v.into(),
+            VAsset::V1(v) => v.into(),
             VAsset::Current(c) => c,
         }
     }
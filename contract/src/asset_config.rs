@@ -19,7 +19,12 @@ const MAX_RATIO: u32 = 10000;
 ///   "can_deposit": true,
 ///   "can_withdraw": true,
 ///   "can_use_as_collateral": true,
-///   "can_borrow": true
+///   "can_borrow": true,
+///   "net_tvl_multiplier": 10000,
+///   "collateral_price_mode": "Spot",
+///   "debt_price_mode": "Spot",
+///   "derived_price": null,
+///   "max_price_staleness_sec": null
 /// }
 /// ```
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
@@ -65,6 +70,32 @@ pub struct AssetConfig {
     /// Example: a multiplier of 5000 means the asset in TVL should only counted as 50%, e.g. if an
     /// asset is not useful for borrowing, but only useful as a collateral.
     pub net_tvl_multiplier: u32,
+    /// The price that is used to value this asset as a collateral.
+    pub collateral_price_mode: PriceMode,
+    /// The price that is used to value this asset as a borrowed asset.
+    pub debt_price_mode: PriceMode,
+    /// If given, the price of this asset is derived from the price of the underlying asset
+    /// multiplied by the exchange rate, e.g. for liquid staking tokens. The oracle price of this
+    /// asset is ignored.
+    pub derived_price: Option<DerivedPrice>,
+    /// The maximum age of the asset price in seconds. E.g. stablecoins can tolerate older prices
+    /// than volatile tokens. If `None`, then the maximum staleness duration from the config is
+    /// used.
+    pub max_price_staleness_sec: Option<DurationSec>,
+}
+
+/// Defines which price is used to value the asset in the account health computation.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum PriceMode {
+    /// The price from the oracle call.
+    Spot,
+    /// The time-weighted average price of the recorded prices.
+    Twap,
+    /// The more conservative of the spot price and the TWAP. The lower price for the collateral
+    /// and the higher price for the borrowed asset.
+    Conservative,
 }
 
 impl AssetConfig {
@@ -74,6 +105,9 @@ impl AssetConfig {
         assert!(self.target_utilization_rate.0 <= self.max_utilization_rate.0);
         // The volatility ratio can't be 100% to avoid free liquidations of such assets.
         assert!(self.volatility_ratio < MAX_RATIO);
+        if let Some(derived_price) = &self.derived_price {
+            derived_price.assert_valid();
+        }
     }
 
     pub fn get_rate(
@@ -120,6 +154,10 @@ mod tests {
             can_use_as_collateral: true,
             can_borrow: true,
             net_tvl_multiplier: 10000,
+            collateral_price_mode: PriceMode::Spot,
+            debt_price_mode: PriceMode::Spot,
+            derived_price: None,
+            max_price_staleness_sec: None,
         }
     }
 
@@ -16,6 +16,11 @@ pub struct AssetFarm {
     /// Inactive rewards
     #[serde(skip_serializing)]
     pub inactive_rewards: LookupMap<TokenId, VAssetFarmReward>,
+    /// The undistributed amounts of the rewards that ended or were cancelled. They are returned to
+    /// the reserves of the reward assets when the farm is saved.
+    #[borsh_skip]
+    #[serde(skip)]
+    pub ended_rewards: Vec<(TokenId, Balance)>,
 }
 
 impl Clone for AssetFarm {
@@ -27,18 +32,21 @@ impl Clone for AssetFarm {
                 &self.inactive_rewards.try_to_vec().unwrap(),
             )
             .unwrap(),
+            ended_rewards: self.ended_rewards.clone(),
         }
     }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum VAssetFarmReward {
+    V0(AssetFarmRewardV0),
     Current(AssetFarmReward),
 }
 
 impl From<VAssetFarmReward> for AssetFarmReward {
     fn from(v: VAssetFarmReward) -> Self {
         match v {
+            VAssetFarmReward::V0(v) => v.into(),
             VAssetFarmReward::Current(c) => c,
         }
     }
@@ -66,6 +74,13 @@ pub struct AssetFarmReward {
     #[serde(with = "u128_dec_format")]
     pub remaining_rewards: Balance,
 
+    /// The time range of the reward distribution.
+    #[serde(flatten)]
+    pub schedule: FarmRewardSchedule,
+    /// How the shares of the accounts are boosted by their xBooster amounts.
+    #[serde(default)]
+    pub boost_curve: BoostCurve,
+
     /// The total number of boosted shares.
     #[serde(with = "u128_dec_format")]
     pub boosted_shares: Balance,
@@ -73,37 +88,171 @@ pub struct AssetFarmReward {
     pub reward_per_share: BigDecimal,
 }
 
+/// The time range of the farm reward distribution given as block timestamps in nanoseconds.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct FarmRewardSchedule {
+    /// When the distribution starts. If `None`, the reward is distributed immediately.
+    #[serde(default, with = "option_u64_dec_format")]
+    pub start_timestamp: Option<Timestamp>,
+    /// When the distribution ends. The undistributed reward is returned to the reserve of the
+    /// reward asset. If `None`, the reward is distributed until the remaining rewards run out.
+    #[serde(default, with = "option_u64_dec_format")]
+    pub end_timestamp: Option<Timestamp>,
+}
+
+impl FarmRewardSchedule {
+    pub fn assert_valid(&self) {
+        if let Some(end_timestamp) = self.end_timestamp {
+            assert!(
+                end_timestamp > env::block_timestamp(),
+                "The end timestamp must be in the future"
+            );
+            if let Some(start_timestamp) = self.start_timestamp {
+                assert!(
+                    start_timestamp < end_timestamp,
+                    "The start timestamp must be before the end timestamp"
+                );
+            }
+        }
+    }
+
+    pub fn has_ended(&self, timestamp: Timestamp) -> bool {
+        self.end_timestamp
+            .map(|end_timestamp| end_timestamp <= timestamp)
+            .unwrap_or(false)
+    }
+}
+
+/// Defines how the xBooster amount of the account boosts its shares in the farm reward.
+/// The boosted shares are computed as `shares * multiplier`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum BoostCurve {
+    /// The multiplier is
+    /// `1 + log(x_booster_amount / booster_base) / log(booster_log_base / booster_base)`.
+    #[default]
+    Logarithmic,
+    /// The multiplier grows linearly with the xBooster amount from `1` and reaches
+    /// `max_multiplier` (multiplied by 10000) at `full_boost_x_booster_amount`.
+    /// E.g. `25000` means the shares can be boosted up to 2.5 times.
+    /// The `full_boost_x_booster_amount` is a fixed xBooster amount rather than a share of the
+    /// total xBooster amount, so it has to be updated as the total xBooster amount changes.
+    CappedLinear {
+        max_multiplier: u32,
+        #[serde(with = "u128_dec_format")]
+        full_boost_x_booster_amount: Balance,
+    },
+    /// The shares are not boosted.
+    None,
+}
+
+impl BoostCurve {
+    pub fn assert_valid(&self) {
+        if let BoostCurve::CappedLinear {
+            max_multiplier,
+            full_boost_x_booster_amount,
+        } = self
+        {
+            assert!(
+                *max_multiplier >= MAX_RATIO,
+                "The max multiplier can't be less than 1"
+            );
+            assert!(
+                *full_boost_x_booster_amount > 0,
+                "The full boost xBooster amount must be positive"
+            );
+        }
+    }
+
+    /// Returns the multiplier of the shares for the given xBooster amount.
+    pub fn compute_multiplier(
+        &self,
+        booster_balance: Balance,
+        booster_log_base: Balance,
+        booster_base: Balance,
+    ) -> BigDecimal {
+        match self {
+            BoostCurve::Logarithmic => {
+                if booster_log_base <= booster_base || booster_balance <= booster_base {
+                    return BigDecimal::one();
+                }
+                let log_base = BigDecimal::from(booster_log_base).div_u128(booster_base);
+                BigDecimal::one()
+                    + BigDecimal::from(booster_balance)
+                        .div_u128(booster_base)
+                        .log(&log_base)
+            }
+            BoostCurve::CappedLinear {
+                max_multiplier,
+                full_boost_x_booster_amount,
+            } => {
+                let booster_balance = std::cmp::min(booster_balance, *full_boost_x_booster_amount);
+                BigDecimal::one()
+                    + BigDecimal::from(booster_balance)
+                        .div_u128(*full_boost_x_booster_amount)
+                        .mul_ratio(max_multiplier - MAX_RATIO)
+            }
+            BoostCurve::None => BigDecimal::one(),
+        }
+    }
+
+    /// Returns the boosted shares for the given shares and xBooster amount.
+    pub fn compute_boosted_shares(
+        &self,
+        shares: Balance,
+        booster_balance: Balance,
+        booster_log_base: Balance,
+        booster_base: Balance,
+    ) -> Balance {
+        self.compute_multiplier(booster_balance, booster_log_base, booster_base)
+            .round_mul_u128(shares)
+    }
+}
+
 impl AssetFarm {
     pub fn update(&mut self, is_view: bool) {
         let block_timestamp = env::block_timestamp();
         if block_timestamp == self.block_timestamp {
             return;
         }
-        let time_diff = block_timestamp - self.block_timestamp;
+        let last_timestamp = self.block_timestamp;
         self.block_timestamp = block_timestamp;
         let mut new_inactive_reward = vec![];
         for (token_id, reward) in self.rewards.iter_mut() {
-            if reward.boosted_shares == 0 {
-                continue;
-            }
-            let acquired_rewards = std::cmp::min(
-                reward.remaining_rewards,
-                u128_ratio(
-                    reward.reward_per_day,
-                    u128::from(time_diff),
-                    u128::from(NANOS_PER_DAY),
-                ),
+            let start_timestamp =
+                std::cmp::max(last_timestamp, reward.schedule.start_timestamp.unwrap_or(0));
+            let end_timestamp = std::cmp::min(
+                block_timestamp,
+                reward.schedule.end_timestamp.unwrap_or(block_timestamp),
             );
-            reward.remaining_rewards -= acquired_rewards;
-            reward.reward_per_share = reward.reward_per_share
-                + BigDecimal::from(acquired_rewards) / BigDecimal::from(reward.boosted_shares);
-            if reward.remaining_rewards == 0 {
+            if reward.boosted_shares > 0 && start_timestamp < end_timestamp {
+                let acquired_rewards = std::cmp::min(
+                    reward.remaining_rewards,
+                    u128_ratio(
+                        reward.reward_per_day,
+                        u128::from(end_timestamp - start_timestamp),
+                        u128::from(NANOS_PER_DAY),
+                    ),
+                );
+                reward.remaining_rewards -= acquired_rewards;
+                reward.reward_per_share = reward.reward_per_share
+                    + BigDecimal::from(acquired_rewards) / BigDecimal::from(reward.boosted_shares);
+            }
+            if reward.remaining_rewards == 0 || reward.schedule.has_ended(block_timestamp) {
                 new_inactive_reward.push(token_id.clone());
             }
         }
         if !is_view {
             for token_id in new_inactive_reward {
-                let reward = self.rewards.remove(&token_id).unwrap();
+                let mut reward = self.rewards.remove(&token_id).unwrap();
+                if reward.remaining_rewards > 0 {
+                    self.ended_rewards
+                        .push((token_id.clone(), reward.remaining_rewards));
+                    reward.remaining_rewards = 0;
+                }
                 self.internal_set_inactive_asset_farm_reward(&token_id, reward);
             }
         }
@@ -135,12 +284,14 @@ impl AssetFarm {
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VAssetFarm {
+    V0(AssetFarmV0),
     Current(AssetFarm),
 }
 
 impl From<VAssetFarm> for AssetFarm {
     fn from(v: VAssetFarm) -> Self {
         match v {
+            VAssetFarm::V0(v) => v.into(),
             VAssetFarm::Current(c) => c,
         }
     }
@@ -171,7 +322,14 @@ impl Contract {
         })
     }
 
-    pub fn internal_set_asset_farm(&mut self, farm_id: &FarmId, asset_farm: AssetFarm) {
+    /// Saves the asset farm and returns the undistributed amounts of the ended rewards to the
+    /// reserves of the reward assets.
+    pub fn internal_set_asset_farm(&mut self, farm_id: &FarmId, mut asset_farm: AssetFarm) {
+        for (token_id, amount) in asset_farm.ended_rewards.drain(..) {
+            let mut asset = self.internal_unwrap_asset(&token_id);
+            asset.reserved += amount;
+            self.internal_set_asset(&token_id, asset);
+        }
         ASSET_FARMS
             .lock()
             .unwrap()
@@ -213,9 +371,166 @@ impl Contract {
         let mut farm_ids = vec![];
         for token_id in self.asset_ids.iter() {
             farm_ids.push(FarmId::Supplied(token_id.clone()));
-            farm_ids.push(FarmId::Borrowed(token_id));
+            farm_ids.push(FarmId::Borrowed(token_id.clone()));
+            farm_ids.push(FarmId::Collateral(token_id));
         }
         farm_ids.push(FarmId::NetTvl);
+        farm_ids.push(FarmId::BoosterStaking);
         self.get_asset_farms(farm_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOSTER_BASE: Balance = 10u128.pow(18);
+
+    fn compute_boosted_shares_f64(
+        shares: Balance,
+        booster_balance: Balance,
+        booster_log_base: Balance,
+    ) -> Balance {
+        let log_base = (booster_log_base as f64) / (BOOSTER_BASE as f64);
+        let extra_shares = ((shares as f64)
+            * ((booster_balance as f64) / (BOOSTER_BASE as f64)).log(log_base))
+            as u128;
+        shares + extra_shares
+    }
+
+    #[test]
+    fn test_boosted_shares_without_booster() {
+        let shares = 10u128.pow(24);
+        assert_eq!(
+            BoostCurve::Logarithmic.compute_boosted_shares(shares, 0, 0, BOOSTER_BASE),
+            shares
+        );
+        assert_eq!(
+            BoostCurve::Logarithmic.compute_boosted_shares(
+                shares,
+                BOOSTER_BASE * 100,
+                0,
+                BOOSTER_BASE
+            ),
+            shares
+        );
+        assert_eq!(
+            BoostCurve::Logarithmic.compute_boosted_shares(
+                shares,
+                BOOSTER_BASE,
+                BOOSTER_BASE * 100,
+                BOOSTER_BASE
+            ),
+            shares
+        );
+        // The log base of 1 or less doesn't boost the shares.
+        assert_eq!(
+            BoostCurve::Logarithmic.compute_boosted_shares(
+                shares,
+                BOOSTER_BASE * 100,
+                BOOSTER_BASE,
+                BOOSTER_BASE
+            ),
+            shares
+        );
+    }
+
+    #[test]
+    fn test_boosted_shares_exact() {
+        let shares = 12345 * 10u128.pow(24);
+        let log_base = BOOSTER_BASE * 20;
+        assert_eq!(
+            BoostCurve::Logarithmic.compute_boosted_shares(
+                shares,
+                BOOSTER_BASE * 20,
+                log_base,
+                BOOSTER_BASE
+            ),
+            shares * 2
+        );
+        assert_eq!(
+            BoostCurve::Logarithmic.compute_boosted_shares(
+                shares,
+                BOOSTER_BASE * 400,
+                log_base,
+                BOOSTER_BASE
+            ),
+            shares * 3
+        );
+    }
+
+    #[test]
+    fn test_boosted_shares_match_f64() {
+        let log_bases = [2, 10, 20, 100, 1000, 100000];
+        let booster_balances = [
+            BOOSTER_BASE + 1,
+            BOOSTER_BASE * 3 / 2,
+            BOOSTER_BASE * 7,
+            BOOSTER_BASE * 123,
+            BOOSTER_BASE * 98765,
+            BOOSTER_BASE * 10u128.pow(9) + 123456789,
+        ];
+        let all_shares = [10u128.pow(6), 10u128.pow(18), 12345 * 10u128.pow(24)];
+        for &log_base in log_bases.iter() {
+            for &booster_balance in booster_balances.iter() {
+                for &shares in all_shares.iter() {
+                    let log_base = log_base * BOOSTER_BASE;
+                    let expected = compute_boosted_shares_f64(shares, booster_balance, log_base);
+                    let actual = BoostCurve::Logarithmic.compute_boosted_shares(
+                        shares,
+                        booster_balance,
+                        log_base,
+                        BOOSTER_BASE,
+                    );
+                    // The f64 result is only precise to about 1e-15 relative error.
+                    let diff = actual.abs_diff(expected);
+                    assert!(
+                        diff <= expected / 10u128.pow(12) + 1,
+                        "Boosted shares {} differ from f64 result {}",
+                        actual,
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_boosted_shares_capped_linear() {
+        let shares = 12345 * 10u128.pow(24);
+        let boost_curve = BoostCurve::CappedLinear {
+            max_multiplier: 25000,
+            full_boost_x_booster_amount: BOOSTER_BASE * 1000,
+        };
+        assert_eq!(
+            boost_curve.compute_boosted_shares(shares, 0, 0, BOOSTER_BASE),
+            shares
+        );
+        assert_eq!(
+            boost_curve.compute_boosted_shares(shares, BOOSTER_BASE * 500, 0, BOOSTER_BASE),
+            shares * 7 / 4
+        );
+        assert_eq!(
+            boost_curve.compute_boosted_shares(shares, BOOSTER_BASE * 1000, 0, BOOSTER_BASE),
+            shares * 5 / 2
+        );
+        assert_eq!(
+            boost_curve.compute_boosted_shares(shares, BOOSTER_BASE * 5000, 0, BOOSTER_BASE),
+            shares * 5 / 2
+        );
+    }
+
+    #[test]
+    fn test_boosted_shares_no_boost() {
+        let shares = 12345 * 10u128.pow(24);
+        assert_eq!(
+            BoostCurve::None.compute_boosted_shares(
+                shares,
+                BOOSTER_BASE * 400,
+                BOOSTER_BASE * 20,
+                BOOSTER_BASE
+            ),
+            shares
+        );
+    }
+}
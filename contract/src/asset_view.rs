@@ -41,6 +41,7 @@ impl Contract {
             .get_asset_farms(vec![
                 FarmId::Supplied(token_id.clone()),
                 FarmId::Borrowed(token_id.clone()),
+                FarmId::Collateral(token_id.clone()),
             ])
             .into_iter()
             .map(|(farm_id, asset_farm)| AssetFarmView {
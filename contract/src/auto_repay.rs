@@ -0,0 +1,53 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Enables or disables the auto-repay for the predecessor account. When enabled, anyone can
+    /// repay the borrowed balance of the account using its supplied balance of the same token.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn account_set_auto_repay(&mut self, enabled: bool) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        account.auto_repay_enabled = enabled;
+        self.internal_set_account(&account_id, account);
+    }
+
+    /// Repays the borrowed balance of the given token of the account using the supplied balance
+    /// (not collateral) of the same token. Returns the repaid amount.
+    /// - Can be called by anyone.
+    /// - Panics if the account didn't enable the auto-repay.
+    /// - Panics if the borrow APR of the asset doesn't exceed the supply APR.
+    pub fn auto_repay(&mut self, account_id: AccountId, token_id: AccountId) -> U128 {
+        let mut account = self.internal_unwrap_account(&account_id);
+        assert!(
+            account.auto_repay_enabled,
+            "The auto-repay is not enabled for the account"
+        );
+        let asset = self.internal_unwrap_asset(&token_id);
+        assert!(
+            asset.get_borrow_apr() > asset.get_supply_apr(),
+            "The borrow APR doesn't exceed the supply APR"
+        );
+
+        let mut account_asset = account.internal_unwrap_asset(&token_id);
+        account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+        account.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+        let amount = self.internal_repay(
+            &mut account_asset,
+            &mut account,
+            &AssetAmount {
+                token_id: token_id.clone(),
+                amount: None,
+                max_amount: None,
+            },
+        );
+        account.internal_set_asset(&token_id, account_asset);
+        events::emit::auto_repay(&account_id, amount, &token_id);
+
+        self.internal_account_apply_affected_farms(&mut account);
+        self.internal_set_account(&account_id, account);
+        amount.into()
+    }
+}
@@ -22,6 +22,9 @@ const NUM_DECIMALS: u8 = 27;
 const BIG_DIVISOR: u128 = 10u128.pow(NUM_DECIMALS as u32);
 const HALF_DIVISOR: u128 = BIG_DIVISOR / 2;
 
+/// The number of binary digits of the fractional part of the logarithm.
+const LOG2_FRACTION_BITS: usize = 64;
+
 pub type LowU128 = U128;
 
 #[derive(Copy, Clone)]
@@ -196,6 +199,13 @@ impl BigDecimal {
         }
     }
 
+    /// Converts the value back into the balance of the token with the given price. The inverse of
+    /// `from_balance_price` that rounds down.
+    pub fn to_balance_price(&self, price: &Price, extra_decimals: u8) -> Balance {
+        let num = self.0 * U384::exp10((price.decimals + extra_decimals) as usize);
+        (num / (U384::from(price.multiplier) * U384::from(BIG_DIVISOR))).as_u128()
+    }
+
     pub fn round_u128(&self) -> u128 {
         ((self.0 + U384::from(HALF_DIVISOR)) / U384::from(BIG_DIVISOR)).as_u128()
     }
@@ -239,6 +249,42 @@ impl BigDecimal {
 
         res
     }
+
+    /// Returns the binary logarithm of the number rounded down to `LOG2_FRACTION_BITS` binary
+    /// digits. Only integer math is used, so the result is deterministic.
+    /// - Panics if the number is less than `1`.
+    pub fn log2(&self) -> Self {
+        assert!(
+            *self >= Self::one(),
+            "The logarithm argument must be at least 1"
+        );
+        let divisor = U384::from(BIG_DIVISOR);
+        let two = divisor * U384::from(2u8);
+        let integer_part = (self.0 / divisor).bits() - 1;
+        // Normalizing the number into `[1, 2)` and computing the fractional part bit by bit.
+        let mut x = self.0 >> integer_part;
+        let mut fraction = U384::zero();
+        for _ in 0..LOG2_FRACTION_BITS {
+            x = (x * x + U384::from(HALF_DIVISOR)) / divisor;
+            fraction <<= 1;
+            if x >= two {
+                x >>= 1;
+                fraction += U384::one();
+            }
+        }
+        Self(U384::from(integer_part) * divisor + ((fraction * divisor) >> LOG2_FRACTION_BITS))
+    }
+
+    /// Returns the logarithm of the number with the given base.
+    /// - Panics if the number is less than `1` or the base is not greater than `1`.
+    pub fn log(&self, base: &Self) -> Self {
+        let base_log2 = base.log2();
+        assert!(
+            base_log2 > Self::zero(),
+            "The logarithm base must be greater than 1"
+        );
+        self.log2() / base_log2
+    }
 }
 
 impl PartialEq<Self> for BigDecimal {
@@ -370,6 +416,52 @@ mod tests {
         almost_eq(val, initial_val * 2, 15);
     }
 
+    #[test]
+    fn test_balance_price_round_trip() {
+        let price = Price {
+            multiplier: 12345,
+            decimals: 28,
+        };
+        let balance = 7 * 10u128.pow(24);
+        let value = BigDecimal::from_balance_price(balance, &price, 0);
+        assert_eq!(value.to_balance_price(&price, 0), balance);
+        let value = BigDecimal::from_balance_price(balance, &price, 6);
+        assert_eq!(value.to_balance_price(&price, 6), balance);
+    }
+
+    #[test]
+    fn test_log2() {
+        assert_eq!(b(1).log2(), BigDecimal::zero());
+        assert_eq!(b(2).log2(), b(1));
+        assert_eq!(b(1024).log2(), b(10));
+        assert_eq!(b(1 << 100).log2(), b(100));
+        // log2(10) = 3.321928094887362347870319429...
+        almost_eq(
+            LowU128::from(b(10).log2()).0,
+            3321928094887362347870319429,
+            18,
+        );
+        // log2(1.5) = 0.584962500721156181453738943...
+        almost_eq(
+            LowU128::from(BigDecimal::from(U128(1500000000000000000000000000)).log2()).0,
+            584962500721156181453738943,
+            18,
+        );
+    }
+
+    #[test]
+    fn test_log() {
+        assert_eq!(b(20).log(&b(20)), b(1));
+        assert_eq!(b(400).log(&b(20)), b(2));
+        almost_eq(LowU128::from(b(1000).log(&b(10))).0, b(3).0.as_u128(), 18);
+    }
+
+    #[test]
+    #[should_panic(expected = "The logarithm argument must be at least 1")]
+    fn test_log2_less_than_one() {
+        BigDecimal::from_ratio(5000).log2();
+    }
+
     #[test]
     fn test_display() {
         assert_eq!("1.0", BigDecimal::one().to_string());
@@ -15,6 +15,26 @@ pub struct BoosterStaking {
     pub unlock_timestamp: u64,
 }
 
+impl BoosterStaking {
+    /// Returns the effective xBooster amount at the given timestamp in nanoseconds. If the booster
+    /// decay is enabled, the amount is limited by the xBooster amount of a new staking of the
+    /// staked booster amount for the remaining duration, so it reaches the staked amount once the
+    /// remaining duration drops to the minimum staking duration.
+    pub fn get_x_booster_amount(&self, config: &Config, timestamp: Timestamp) -> Balance {
+        if !config.booster_decay_enabled {
+            return self.x_booster_amount;
+        }
+        let remaining_duration_ns = self.unlock_timestamp.saturating_sub(timestamp);
+        let decayed_x_booster_amount =
+            if remaining_duration_ns > to_nano(config.minimum_staking_duration_sec) {
+                compute_x_booster_amount(config, self.staked_booster_amount, remaining_duration_ns)
+            } else {
+                self.staked_booster_amount
+            };
+        std::cmp::min(self.x_booster_amount, decayed_x_booster_amount)
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     /// Stakes a given amount (or all supplied) booster token for a given duration in seconds.
@@ -25,12 +45,6 @@ impl Contract {
         assert_one_yocto();
         let config = self.internal_config();
 
-        assert!(
-            duration >= config.minimum_staking_duration_sec
-                && duration <= config.maximum_staking_duration_sec,
-            "Duration is out of range"
-        );
-
         let account_id = env::predecessor_account_id();
         let mut account = self.internal_unwrap_account(&account_id);
 
@@ -59,6 +73,70 @@ impl Contract {
         asset.supplied.withdraw(shares, amount);
         self.internal_set_asset(&booster_token_id, asset);
 
+        self.internal_booster_stake(&account_id, account, amount, duration);
+    }
+
+    /// Extends the staking of the booster token to the given duration in seconds from now without
+    /// staking more tokens. The xBooster amount is recomputed for the new duration, but it never
+    /// decreases.
+    /// - Panics if the account doesn't have staked booster token.
+    /// - Panics if the new duration is shorter than the current remaining staking duration.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn account_extend_booster_staking(&mut self, duration: DurationSec) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let account = self.internal_unwrap_account(&account_id);
+        assert!(account.booster_staking.is_some(), "No staked booster token");
+
+        self.internal_booster_stake(&account_id, account, 0, duration);
+    }
+
+    /// Unstakes the given amount (or all) of the staked booster token and deposits it to the
+    /// supplied balance. The xBooster amount is reduced proportionally to the unstaked amount.
+    /// - Panics if the staking is not unlocked yet.
+    /// - Panics if the account voted on a proposal that is still in the voting.
+    /// - Panics if the amount is zero or larger than the staked amount.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn account_unstake_booster(&mut self, amount: Option<U128>) {
+        assert_one_yocto();
+        self.internal_booster_unstake(amount.map(|a| a.0), false);
+    }
+
+    /// Unstakes the given amount (or all) of the staked booster token before the staking is
+    /// unlocked. The penalty is charged from the unstaked amount proportionally to the remaining
+    /// staking duration and is added to the reserves of the booster token. The rest is deposited
+    /// to the supplied balance.
+    /// - Panics if the early unstaking is disabled.
+    /// - Panics if the account voted on a proposal that is still in the voting.
+    /// - Panics if the amount is zero or larger than the staked amount.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn account_early_unstake_booster(&mut self, amount: Option<U128>) {
+        assert_one_yocto();
+        self.internal_booster_unstake(amount.map(|a| a.0), true);
+    }
+}
+
+impl Contract {
+    /// Stakes the given amount of the booster token, that is already withdrawn from the supplied
+    /// balance, for the given duration in seconds. Recomputes the xBooster amount and the unlock
+    /// timestamp of the staking and re-applies all farms of the account.
+    fn internal_booster_stake(
+        &mut self,
+        account_id: &AccountId,
+        mut account: Account,
+        amount: Balance,
+        duration: DurationSec,
+    ) {
+        let config = self.internal_config();
+        assert!(
+            duration >= config.minimum_staking_duration_sec
+                && duration <= config.maximum_staking_duration_sec,
+            "Duration is out of range"
+        );
+
         // Computing amount of the new xBooster token and new unlock timestamp.
         let timestamp = env::block_timestamp();
         let new_duration_ns = sec_to_nano(duration);
@@ -88,7 +166,7 @@ impl Contract {
         booster_staking.x_booster_amount += extra_x_booster_amount;
 
         events::emit::booster_stake(
-            &account_id,
+            account_id,
             amount,
             duration,
             extra_x_booster_amount,
@@ -100,40 +178,90 @@ impl Contract {
         account
             .affected_farms
             .extend(account.get_all_potential_farms());
-        account.add_affected_farm(FarmId::Supplied(config.booster_token_id.clone()));
+        account.add_affected_farm(FarmId::Supplied(config.booster_token_id));
         self.internal_account_apply_affected_farms(&mut account);
-        self.internal_set_account(&account_id, account);
+        self.internal_set_account(account_id, account);
     }
 
-    #[payable]
-    pub fn account_unstake_booster(&mut self) {
-        assert_one_yocto();
-
+    /// Unstakes the given amount (or all) of the staked booster token of the predecessor account.
+    /// If `early` is set, then the staking doesn't have to be unlocked, but the early unstake
+    /// penalty is charged and added to the reserves of the booster token.
+    fn internal_booster_unstake(&mut self, amount: Option<Balance>, early: bool) {
         let config = self.internal_config();
         let account_id = env::predecessor_account_id();
         let mut account = self.internal_unwrap_account(&account_id);
 
         let timestamp = env::block_timestamp();
-        let booster_staking = account
+        assert!(
+            account.booster_vote_lock_timestamp <= timestamp,
+            "The staking is locked until the voting on the proposals ends"
+        );
+        let mut booster_staking = account
             .booster_staking
             .take()
             .expect("No staked booster token");
+        let penalty_ratio = if early {
+            config
+                .booster_early_unstake_penalty
+                .expect("The early unstaking is disabled")
+        } else {
+            assert!(
+                booster_staking.unlock_timestamp <= timestamp,
+                "The staking is not unlocked yet"
+            );
+            0
+        };
+
+        let amount = amount.unwrap_or(booster_staking.staked_booster_amount);
         assert!(
-            booster_staking.unlock_timestamp <= timestamp,
-            "The staking is not unlocked yet"
+            amount > 0 && amount <= booster_staking.staked_booster_amount,
+            "The amount should be greater than zero and not exceed the staked amount"
+        );
+        let x_booster_amount = u128_ratio(
+            booster_staking.x_booster_amount,
+            amount,
+            booster_staking.staked_booster_amount,
+        );
+        booster_staking.staked_booster_amount -= amount;
+        booster_staking.x_booster_amount -= x_booster_amount;
+
+        let remaining_duration_ns = std::cmp::min(
+            booster_staking.unlock_timestamp.saturating_sub(timestamp),
+            to_nano(config.maximum_staking_duration_sec),
+        );
+        let penalty_amount = u128_ratio(
+            amount,
+            u128::from(penalty_ratio) * u128::from(remaining_duration_ns),
+            u128::from(MAX_RATIO) * u128::from(to_nano(config.maximum_staking_duration_sec)),
         );
+        if penalty_amount > 0 {
+            let mut asset = self.internal_unwrap_asset(&config.booster_token_id);
+            asset.reserved += penalty_amount;
+            self.internal_set_asset(&config.booster_token_id, asset);
+        }
 
         self.internal_deposit(
             &mut account,
             &config.booster_token_id,
-            booster_staking.staked_booster_amount,
+            amount - penalty_amount,
+        );
+
+        events::emit::booster_unstake(
+            &account_id,
+            amount,
+            x_booster_amount,
+            penalty_amount,
+            &booster_staking,
         );
 
-        events::emit::booster_unstake(&account_id, &booster_staking);
+        if booster_staking.staked_booster_amount > 0 {
+            account.booster_staking.replace(booster_staking);
+        }
 
         account
             .affected_farms
             .extend(account.get_all_potential_farms());
+        account.add_affected_farm(FarmId::BoosterStaking);
         self.internal_account_apply_affected_farms(&mut account);
         self.internal_set_account(&account_id, account);
     }
@@ -3,13 +3,14 @@ use crate::*;
 pub const MIN_BOOSTER_MULTIPLIER: u32 = 10000;
 
 /// Contract config
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Config {
     /// The account ID of the oracle contract
     pub oracle_account_id: AccountId,
 
     /// The account ID of the contract owner that allows to modify config, assets and use reserves.
+    /// The owner is granted all roles when the contract is initialized or migrated.
     pub owner_id: AccountId,
 
     /// The account ID of the booster token contract.
@@ -44,6 +45,70 @@ pub struct Config {
     /// Whether an account with bad debt can be liquidated using reserves.
     /// The account should have borrowed sum larger than the collateral sum.
     pub force_closing_enabled: bool,
+
+    /// The account ID of the whitelisted DEX contract that is used to swap assets on behalf of
+    /// accounts. If `None`, then swaps are disabled.
+    /// The DEX has to send the output tokens within the promise chain of `ft_on_transfer`,
+    /// see `common::SwapMsg`. Otherwise the swap stays pending until the output is received.
+    pub dex_account_id: Option<AccountId>,
+
+    /// The fee ratio that is charged when the collateral is converted into the repayment of the
+    /// borrowed balance using reserves. E.g. 100 means 1%.
+    /// If `None`, then deleveraging using reserves is disabled.
+    pub reserve_deleverage_fee: Option<u32>,
+
+    /// The account IDs of the additional oracle contracts. The prices from the oracle call are
+    /// aggregated with the recent prices committed by the other oracles using the median.
+    pub secondary_oracle_account_ids: Vec<AccountId>,
+
+    /// The maximum deviation of the oracle prices from their median. If the price of any oracle
+    /// deviates more, the asset price is rejected. E.g. 500 means 5%.
+    pub max_oracle_price_deviation: u32,
+
+    /// The minimum number of fresh oracle prices of an asset, including the price from the oracle
+    /// call, that are required to aggregate the asset price. If there are fewer, the asset price
+    /// is rejected.
+    pub min_oracle_sources: u32,
+
+    /// The maximum change of the asset price from the last stored price within the price change
+    /// window. If the price changes more, borrowing and liquidations involving the asset are
+    /// rejected. E.g. 2000 means 20%.
+    pub max_price_change: u32,
+
+    /// The duration in seconds since the last stored price during which the price changes are
+    /// checked. If 0, the price changes are not checked.
+    pub price_change_window_sec: DurationSec,
+
+    /// The duration in seconds of the time-weighted average price.
+    pub twap_duration_sec: DurationSec,
+
+    /// The account ID of the pull oracle price feed contract. The prices are read from the feed
+    /// using a view call. If `None`, then the price feed is disabled.
+    pub price_feed_account_id: Option<AccountId>,
+
+    /// The maximum confidence interval of the price feed price relative to the price. If the
+    /// confidence interval is wider, the asset price is rejected. E.g. 100 means 1%.
+    pub max_price_feed_confidence: u32,
+
+    /// Whether the effective xBooster amount decays linearly toward the staked booster amount as
+    /// the unlock timestamp approaches. The effective amount is what a new staking of the same
+    /// amount would give for the remaining duration, but never more than the xBooster amount.
+    pub booster_decay_enabled: bool,
+
+    /// The penalty ratio charged for unstaking the booster token before the staking is unlocked
+    /// with the maximum staking duration remaining. The penalty decreases linearly with the
+    /// remaining staking duration and goes to the reserves of the booster token.
+    /// E.g. 5000 means 50%. If `None`, then the early unstaking is disabled.
+    pub booster_early_unstake_penalty: Option<u32>,
+
+    /// The parameters of the governance by the xBooster holders. If `None`, then the governance
+    /// is disabled.
+    pub governance: Option<GovernanceConfig>,
+
+    /// The delay in seconds before the queued owner actions can be executed. Applies to
+    /// `update_config`, `add_asset`, `update_asset` and `upgrade`. If 0, then these owner actions
+    /// take effect immediately.
+    pub owner_action_delay_sec: DurationSec,
 }
 
 impl Config {
@@ -56,6 +121,41 @@ impl Config {
             self.x_booster_multiplier_at_maximum_staking_duration >= MIN_BOOSTER_MULTIPLIER,
             "xBooster multiplier should be no less than 100%"
         );
+        assert!(
+            self.max_oracle_price_deviation <= MAX_RATIO,
+            "The maximum oracle price deviation can't exceed 100%"
+        );
+        assert!(
+            self.min_oracle_sources >= 1
+                && self.min_oracle_sources as usize <= self.secondary_oracle_account_ids.len() + 1,
+            "The minimum number of oracle sources must be between 1 and the number of oracles"
+        );
+        assert!(
+            self.max_price_change <= MAX_RATIO,
+            "The maximum price change can't exceed 100%"
+        );
+        assert!(
+            self.max_price_feed_confidence <= MAX_RATIO,
+            "The maximum price feed confidence can't exceed 100%"
+        );
+        if let Some(booster_early_unstake_penalty) = self.booster_early_unstake_penalty {
+            assert!(
+                booster_early_unstake_penalty <= MAX_RATIO,
+                "The booster early unstake penalty can't exceed 100%"
+            );
+        }
+        if let Some(reserve_deleverage_fee) = self.reserve_deleverage_fee {
+            assert!(
+                reserve_deleverage_fee <= MAX_RATIO,
+                "The reserve deleverage fee can't exceed 100%"
+            );
+        }
+    }
+
+    /// Whether the given account ID is one of the oracles.
+    pub fn is_oracle_account_id(&self, account_id: &AccountId) -> bool {
+        &self.oracle_account_id == account_id
+            || self.secondary_oracle_account_ids.contains(account_id)
     }
 }
 
@@ -68,12 +168,98 @@ impl Contract {
         self.internal_config().oracle_account_id.into()
     }
 
-    pub fn assert_owner(&self) {
-        assert_eq!(
-            &env::predecessor_account_id(),
-            &self.internal_config().owner_id,
-            "Not an owner"
+    pub fn internal_update_config(&mut self, config: Config) {
+        config.assert_valid();
+        self.config.set(&config);
+    }
+
+    pub fn internal_add_asset(&mut self, token_id: &TokenId, asset_config: AssetConfig) {
+        asset_config.assert_valid();
+        assert!(self.asset_ids.insert(token_id));
+        self.internal_set_asset(token_id, Asset::new(env::block_timestamp(), asset_config))
+    }
+
+    /// Updates the asset config. The borrowing of the asset is kept disabled if it's disabled, so
+    /// a queued update can't undo an emergency pause. It's enabled by `update_asset_can_borrow`.
+    pub fn internal_update_asset(&mut self, token_id: &TokenId, mut asset_config: AssetConfig) {
+        asset_config.assert_valid();
+        let mut asset = self.internal_unwrap_asset(token_id);
+        asset_config.can_borrow &= asset.config.can_borrow;
+        if asset.config.extra_decimals != asset_config.extra_decimals {
+            assert!(
+                asset.borrowed.balance == 0 && asset.supplied.balance == 0 && asset.reserved == 0,
+                "Can't change extra decimals if any of the balances are not 0"
+            );
+        }
+        asset.config = asset_config;
+        self.internal_set_asset(token_id, asset);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn internal_add_asset_farm_reward(
+        &mut self,
+        farm_id: FarmId,
+        reward_token_id: TokenId,
+        new_reward_per_day: Balance,
+        new_booster_log_base: Balance,
+        reward_amount: Balance,
+        schedule: Option<FarmRewardSchedule>,
+        boost_curve: Option<BoostCurve>,
+    ) {
+        if let Some(schedule) = &schedule {
+            schedule.assert_valid();
+        }
+        if let Some(boost_curve) = &boost_curve {
+            boost_curve.assert_valid();
+        }
+        match &farm_id {
+            FarmId::Supplied(token_id)
+            | FarmId::Borrowed(token_id)
+            | FarmId::Collateral(token_id) => {
+                assert!(self.assets.contains_key(token_id));
+            }
+            FarmId::NetTvl | FarmId::BoosterStaking => {}
+        };
+        let mut reward_asset = self.internal_unwrap_asset(&reward_token_id);
+        assert!(
+            reward_asset.reserved >= reward_amount
+                && reward_asset.available_amount() >= reward_amount,
+            "Not enough reserved reward balance"
         );
+        reward_asset.reserved -= reward_amount;
+        self.internal_set_asset(&reward_token_id, reward_asset);
+        let mut asset_farm = self
+            .internal_get_asset_farm(&farm_id, false)
+            .unwrap_or_else(|| AssetFarm {
+                block_timestamp: env::block_timestamp(),
+                rewards: HashMap::new(),
+                inactive_rewards: LookupMap::new(StorageKey::InactiveAssetFarmRewards {
+                    farm_id: farm_id.clone(),
+                }),
+                ended_rewards: vec![],
+            });
+
+        let mut asset_farm_reward = asset_farm
+            .rewards
+            .remove(&reward_token_id)
+            .or_else(|| asset_farm.internal_remove_inactive_asset_farm_reward(&reward_token_id))
+            .unwrap_or_default();
+        asset_farm_reward.reward_per_day = new_reward_per_day;
+        asset_farm_reward.booster_log_base = new_booster_log_base;
+        asset_farm_reward.remaining_rewards += reward_amount;
+        if let Some(schedule) = schedule {
+            asset_farm_reward.schedule = schedule;
+        } else if asset_farm_reward.schedule.has_ended(env::block_timestamp()) {
+            // The ended schedule would end the topped up reward again, so it's reset.
+            asset_farm_reward.schedule = FarmRewardSchedule::default();
+        }
+        if let Some(boost_curve) = boost_curve {
+            asset_farm_reward.boost_curve = boost_curve;
+        }
+        asset_farm
+            .rewards
+            .insert(reward_token_id, asset_farm_reward);
+        self.internal_set_asset_farm(&farm_id, asset_farm);
     }
 }
 
@@ -86,61 +272,88 @@ impl Contract {
 
     /// Updates the current config.
     /// - Requires one yoctoNEAR.
-    /// - Requires to be called by the contract owner.
+    /// - Requires the admin role.
     #[payable]
     pub fn update_config(&mut self, config: Config) {
         assert_one_yocto();
-        self.assert_owner();
-        config.assert_valid();
-        self.config.set(&config);
+        self.assert_role(Role::Admin);
+        self.internal_owner_action(TimelockAction::UpdateConfig { config });
     }
 
     /// Adds an asset with a given token_id and a given asset_config.
     /// - Panics if the asset config is invalid.
     /// - Panics if an asset with the given token_id already exists.
     /// - Requires one yoctoNEAR.
-    /// - Requires to be called by the contract owner.
+    /// - Requires the risk manager role.
     #[payable]
     pub fn add_asset(&mut self, token_id: AccountId, asset_config: AssetConfig) {
         assert_one_yocto();
-        asset_config.assert_valid();
-        self.assert_owner();
-        assert!(self.asset_ids.insert(&token_id));
-        self.internal_set_asset(&token_id, Asset::new(env::block_timestamp(), asset_config))
+        self.assert_role(Role::RiskManager);
+        self.internal_owner_action(TimelockAction::AddAsset {
+            token_id,
+            asset_config,
+        });
     }
 
     /// Updates the asset config for the asset with the a given token_id.
+    /// - The config can't enable borrowing of the asset, if it's disabled. Use
+    ///   `update_asset_can_borrow` instead.
     /// - Panics if the asset config is invalid.
     /// - Panics if an asset with the given token_id doesn't exist.
     /// - Requires one yoctoNEAR.
-    /// - Requires to be called by the contract owner.
+    /// - Requires the risk manager role.
     #[payable]
     pub fn update_asset(&mut self, token_id: AccountId, asset_config: AssetConfig) {
         assert_one_yocto();
-        asset_config.assert_valid();
-        self.assert_owner();
+        self.assert_role(Role::RiskManager);
+        self.internal_owner_action(TimelockAction::UpdateAsset {
+            token_id,
+            asset_config,
+        });
+    }
+
+    /// Enables or disables borrowing of the asset with the given token_id without updating the
+    /// rest of the asset config, e.g. to quickly freeze borrowing during an oracle outage.
+    /// Disabling borrowing takes effect immediately, while enabling it is an owner action that is
+    /// queued if the owner actions are timelocked.
+    /// - Panics if an asset with the given token_id doesn't exist.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the risk manager role, or the guardian role to disable borrowing.
+    #[payable]
+    pub fn update_asset_can_borrow(&mut self, token_id: AccountId, can_borrow: bool) {
+        assert_one_yocto();
         let mut asset = self.internal_unwrap_asset(&token_id);
-        if asset.config.extra_decimals != asset_config.extra_decimals {
-            assert!(
-                asset.borrowed.balance == 0 && asset.supplied.balance == 0 && asset.reserved == 0,
-                "Can't change extra decimals if any of the balances are not 0"
-            );
+        if can_borrow {
+            self.assert_role(Role::RiskManager);
+            self.internal_owner_action(TimelockAction::EnableAssetBorrowing { token_id });
+        } else {
+            if !self.has_role(&env::predecessor_account_id(), Role::Guardian) {
+                self.assert_role(Role::RiskManager);
+            }
+            asset.config.can_borrow = false;
+            self.internal_set_asset(&token_id, asset);
         }
-        asset.config = asset_config;
-        self.internal_set_asset(&token_id, asset);
     }
 
     /// Adds an asset farm reward for the farm with a given farm_id. The reward is of token_id with
     /// the new reward per day amount and a new booster log base. The extra amount of reward is
-    /// taken from the asset reserved balance.
+    /// taken from the asset reserved balance. The optional schedule defines when the distribution
+    /// of the reward starts and ends. If not given, the existing schedule of the reward is kept, so
+    /// a new reward is distributed immediately until it runs out. The schedule that has already
+    /// ended is not kept, so the topped up reward is distributed again. The optional boost curve
+    /// defines how the xBooster amount boosts the shares. If not given, the existing curve of the
+    /// reward is kept, so a new reward uses the logarithmic curve.
     /// - The booster log base should include decimals of the token for better precision of the log
     ///    base. For example, if token decimals is `6` the log base of `10_500_000` will be `10.5`.
     /// - Panics if the farm asset token_id doesn't exists.
     /// - Panics if an asset with the given token_id doesn't exists.
     /// - Panics if an asset with the given token_id doesn't have enough reserved balance.
+    /// - Panics if the schedule ends in the past or starts after it ends.
+    /// - Panics if the boost curve is invalid.
     /// - Requires one yoctoNEAR.
-    /// - Requires to be called by the contract owner.
+    /// - Requires the farm manager role.
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     pub fn add_asset_farm_reward(
         &mut self,
         farm_id: FarmId,
@@ -148,45 +361,87 @@ impl Contract {
         new_reward_per_day: U128,
         new_booster_log_base: U128,
         reward_amount: U128,
+        schedule: Option<FarmRewardSchedule>,
+        boost_curve: Option<BoostCurve>,
     ) {
         assert_one_yocto();
-        self.assert_owner();
-        match &farm_id {
-            FarmId::Supplied(token_id) | FarmId::Borrowed(token_id) => {
-                assert!(self.assets.contains_key(token_id));
-            }
-            FarmId::NetTvl => {}
-        };
-        let reward_token_id: TokenId = reward_token_id.into();
-        let mut reward_asset = self.internal_unwrap_asset(&reward_token_id);
-        assert!(
-            reward_asset.reserved >= reward_amount.0
-                && reward_asset.available_amount() >= reward_amount.0,
-            "Not enough reserved reward balance"
+        self.assert_role(Role::FarmManager);
+        self.internal_add_asset_farm_reward(
+            farm_id,
+            reward_token_id,
+            new_reward_per_day.0,
+            new_booster_log_base.0,
+            reward_amount.0,
+            schedule,
+            boost_curve,
         );
-        reward_asset.reserved -= reward_amount.0;
-        self.internal_set_asset(&reward_token_id, reward_asset);
-        let mut asset_farm = self
-            .internal_get_asset_farm(&farm_id, false)
-            .unwrap_or_else(|| AssetFarm {
-                block_timestamp: env::block_timestamp(),
-                rewards: HashMap::new(),
-                inactive_rewards: LookupMap::new(StorageKey::InactiveAssetFarmRewards {
-                    farm_id: farm_id.clone(),
-                }),
-            });
+    }
 
+    /// Updates the boost curve of the active asset farm reward. The boosted shares of the accounts
+    /// are recomputed with the new curve the next time their farms are updated. Until then, the
+    /// total boosted shares of the reward mix both curves, so the change only has its full effect
+    /// once every account of the farm is poked with `account_farm_poke`.
+    /// - Panics if the farm reward is not active.
+    /// - Panics if the boost curve is invalid.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the farm manager role.
+    #[payable]
+    pub fn update_asset_farm_reward_boost_curve(
+        &mut self,
+        farm_id: FarmId,
+        reward_token_id: AccountId,
+        boost_curve: BoostCurve,
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::FarmManager);
+        boost_curve.assert_valid();
+        let mut asset_farm = self.internal_unwrap_asset_farm(&farm_id, false);
+        asset_farm
+            .rewards
+            .get_mut(&reward_token_id)
+            .expect("The farm reward is not active")
+            .boost_curve = boost_curve;
+        self.internal_set_asset_farm(&farm_id, asset_farm);
+    }
+
+    /// Cancels the given amount of the remaining rewards of the active asset farm reward and
+    /// returns it to the reserve of the reward asset. If the amount is not given, all remaining
+    /// rewards are cancelled. Once there are no remaining rewards, the reward becomes inactive.
+    /// The rewards distributed before the cancellation can still be claimed by the accounts.
+    /// - Panics if the farm reward is not active.
+    /// - Panics if the amount is larger than the remaining rewards.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the farm manager role.
+    #[payable]
+    pub fn cancel_asset_farm_reward(
+        &mut self,
+        farm_id: FarmId,
+        reward_token_id: AccountId,
+        amount: Option<U128>,
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::FarmManager);
+        let mut asset_farm = self.internal_unwrap_asset_farm(&farm_id, false);
         let mut asset_farm_reward = asset_farm
             .rewards
             .remove(&reward_token_id)
-            .or_else(|| asset_farm.internal_remove_inactive_asset_farm_reward(&reward_token_id))
-            .unwrap_or_default();
-        asset_farm_reward.reward_per_day = new_reward_per_day.into();
-        asset_farm_reward.booster_log_base = new_booster_log_base.into();
-        asset_farm_reward.remaining_rewards += reward_amount.0;
-        asset_farm
-            .rewards
-            .insert(reward_token_id, asset_farm_reward);
+            .expect("The farm reward is not active");
+        let amount = amount
+            .map(|amount| amount.0)
+            .unwrap_or(asset_farm_reward.remaining_rewards);
+        assert!(
+            amount <= asset_farm_reward.remaining_rewards,
+            "The amount exceeds the remaining rewards"
+        );
+        asset_farm_reward.remaining_rewards -= amount;
+        if asset_farm_reward.remaining_rewards == 0 {
+            asset_farm.internal_set_inactive_asset_farm_reward(&reward_token_id, asset_farm_reward);
+        } else {
+            asset_farm
+                .rewards
+                .insert(reward_token_id.clone(), asset_farm_reward);
+        }
+        asset_farm.ended_rewards.push((reward_token_id, amount));
         self.internal_set_asset_farm(&farm_id, asset_farm);
     }
 }
@@ -0,0 +1,125 @@
+use crate::*;
+use near_sdk::{serde_json, PromiseResult};
+
+const GAS_FOR_GET_EXCHANGE_RATE: Gas = Gas(Gas::ONE_TERA.0 * 10);
+const GAS_FOR_AFTER_GET_EXCHANGE_RATE: Gas = Gas(Gas::ONE_TERA.0 * 10);
+
+/// The maximum number of decimals of the exchange rate read from the contract.
+const MAX_EXCHANGE_RATE_DECIMALS: u8 = 38;
+
+/// Defines how the price of a derivative token is derived from the price of the underlying token.
+/// The price is computed as `underlying_price * exchange_rate`, where the exchange rate is the
+/// amount of the underlying token per one unit of the derivative token, both without decimals.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct DerivedPrice {
+    pub underlying_token_id: TokenId,
+    pub exchange_rate: ExchangeRateSource,
+}
+
+/// Defines where the exchange rate of the derivative token comes from.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum ExchangeRateSource {
+    /// The exchange rate is provided by the oracle as the price of the given asset ID.
+    Oracle { rate_asset_id: TokenId },
+    /// The exchange rate is read from the view method without arguments of the given contract,
+    /// e.g. a liquid staking pool. The method should return the rate as `U128` with the given
+    /// number of decimals. The rate is cached by calling `update_exchange_rate`.
+    Contract {
+        contract_id: AccountId,
+        method_name: String,
+        decimals: u8,
+    },
+}
+
+/// The exchange rate read from the contract at the given block timestamp.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExchangeRate {
+    pub rate: Price,
+    #[serde(with = "u64_dec_format")]
+    pub timestamp: Timestamp,
+}
+
+impl DerivedPrice {
+    pub fn assert_valid(&self) {
+        if let ExchangeRateSource::Contract { decimals, .. } = &self.exchange_rate {
+            assert!(
+                *decimals <= MAX_EXCHANGE_RATE_DECIMALS,
+                "Too many exchange rate decimals"
+            );
+        }
+    }
+}
+
+#[ext_contract(ext_derived_price_self)]
+trait ExtDerivedPriceSelf {
+    fn after_get_exchange_rate(&mut self, token_id: TokenId);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Reads the exchange rate of the derivative token from its exchange rate contract and caches
+    /// it to be used in the price computation. Can be called by anyone.
+    /// - Panics if the exchange rate of the asset doesn't come from a contract.
+    pub fn update_exchange_rate(&mut self, token_id: AccountId) -> Promise {
+        let asset = self.internal_unwrap_asset(&token_id);
+        let (contract_id, method_name) = match asset.config.derived_price.map(|d| d.exchange_rate) {
+            Some(ExchangeRateSource::Contract {
+                contract_id,
+                method_name,
+                ..
+            }) => (contract_id, method_name),
+            _ => env::panic_str("The exchange rate of the asset doesn't come from a contract"),
+        };
+        Promise::new(contract_id)
+            .function_call(
+                method_name,
+                b"{}".to_vec(),
+                NO_DEPOSIT,
+                GAS_FOR_GET_EXCHANGE_RATE,
+            )
+            .then(ext_derived_price_self::after_get_exchange_rate(
+                token_id,
+                env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_AFTER_GET_EXCHANGE_RATE,
+            ))
+    }
+
+    /// Callback after the exchange rate was read from the contract.
+    #[private]
+    pub fn after_get_exchange_rate(&mut self, token_id: TokenId) {
+        let asset = self.internal_unwrap_asset(&token_id);
+        let decimals = match asset.config.derived_price.map(|d| d.exchange_rate) {
+            Some(ExchangeRateSource::Contract { decimals, .. }) => decimals,
+            _ => env::panic_str("The exchange rate of the asset doesn't come from a contract"),
+        };
+        let rate: U128 = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice(&value).expect("Can't parse the exchange rate")
+            }
+            _ => env::panic_str("Failed to read the exchange rate"),
+        };
+        assert!(rate.0 > 0, "The exchange rate must be positive");
+        self.exchange_rates.insert(
+            token_id,
+            ExchangeRate {
+                rate: Price {
+                    multiplier: rate.0,
+                    decimals,
+                },
+                timestamp: env::block_timestamp(),
+            },
+        );
+    }
+
+    /// Returns the cached exchange rate of the derivative token.
+    pub fn get_exchange_rate(&self, token_id: AccountId) -> Option<ExchangeRate> {
+        self.exchange_rates.get(&token_id).cloned()
+    }
+}
@@ -2,6 +2,7 @@ use crate::*;
 
 pub mod emit {
     use super::*;
+    use near_sdk::json_types::U64;
     use near_sdk::serde_json::json;
 
     #[derive(Serialize)]
@@ -123,6 +124,17 @@ pub mod emit {
         );
     }
 
+    pub fn auto_repay(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "auto_repay",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
     pub fn liquidate(
         account_id: &AccountId,
         liquidation_account_id: &AccountId,
@@ -175,14 +187,300 @@ pub mod emit {
         );
     }
 
-    pub fn booster_unstake(account_id: &AccountId, booster_staking: &BoosterStaking) {
+    pub fn farm_reward_claimed(
+        account_id: &AccountId,
+        farm_id: &FarmId,
+        token_id: &TokenId,
+        amount: Balance,
+        total_claimed_amount: Balance,
+    ) {
+        log_event(
+            "farm_reward_claimed",
+            json!({
+                "account_id": account_id,
+                "farm_id": farm_id,
+                "token_id": token_id,
+                "amount": U128(amount),
+                "total_claimed_amount": U128(total_claimed_amount),
+            }),
+        );
+    }
+
+    pub fn booster_unstake(
+        account_id: &AccountId,
+        amount: Balance,
+        x_booster_amount: Balance,
+        penalty_amount: Balance,
+        booster_staking: &BoosterStaking,
+    ) {
         log_event(
             "booster_unstake",
             json!({
                 "account_id": account_id,
+                "booster_amount": U128(amount),
+                "x_booster_amount": U128(x_booster_amount),
+                "penalty_amount": U128(penalty_amount),
                 "total_booster_amount": U128(booster_staking.staked_booster_amount),
                 "total_x_booster_amount": U128(booster_staking.x_booster_amount),
             }),
         );
     }
+
+    pub fn proposal_created(account_id: &AccountId, proposal_id: ProposalId) {
+        log_event(
+            "proposal_created",
+            json!({
+                "account_id": account_id,
+                "proposal_id": proposal_id,
+            }),
+        );
+    }
+
+    pub fn proposal_vote(
+        account_id: &AccountId,
+        proposal_id: ProposalId,
+        approve: bool,
+        voting_power: Balance,
+    ) {
+        log_event(
+            "proposal_vote",
+            json!({
+                "account_id": account_id,
+                "proposal_id": proposal_id,
+                "approve": approve,
+                "voting_power": U128(voting_power),
+            }),
+        );
+    }
+
+    pub fn proposal_executed(proposal_id: ProposalId) {
+        log_event(
+            "proposal_executed",
+            json!({
+                "proposal_id": proposal_id,
+            }),
+        );
+    }
+
+    pub fn proposal_cancelled(proposal_id: ProposalId) {
+        log_event(
+            "proposal_cancelled",
+            json!({
+                "proposal_id": proposal_id,
+            }),
+        );
+    }
+
+    pub fn timelock_action_queued(action_id: TimelockActionId, eta: Timestamp) {
+        log_event(
+            "timelock_action_queued",
+            json!({
+                "action_id": action_id,
+                "eta": U64(eta),
+            }),
+        );
+    }
+
+    pub fn timelock_action_executed(action_id: TimelockActionId) {
+        log_event(
+            "timelock_action_executed",
+            json!({
+                "action_id": action_id,
+            }),
+        );
+    }
+
+    pub fn timelock_action_cancelled(action_id: TimelockActionId) {
+        log_event(
+            "timelock_action_cancelled",
+            json!({
+                "action_id": action_id,
+            }),
+        );
+    }
+
+    pub fn role_granted(account_id: &AccountId, role: Role) {
+        log_event(
+            "role_granted",
+            json!({
+                "account_id": account_id,
+                "role": role,
+            }),
+        );
+    }
+
+    pub fn role_revoked(account_id: &AccountId, role: Role) {
+        log_event(
+            "role_revoked",
+            json!({
+                "account_id": account_id,
+                "role": role,
+            }),
+        );
+    }
+
+    pub fn swap_collateral(
+        account_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+        min_amount_out: Balance,
+        token_out: &TokenId,
+    ) {
+        log_event(
+            "swap_collateral",
+            json!({
+                "account_id": account_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+                "min_amount_out": U128(min_amount_out),
+                "token_out": token_out,
+            }),
+        );
+    }
+
+    pub fn swap_debt(
+        account_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+        min_amount_out: Balance,
+        token_out: &TokenId,
+    ) {
+        log_event(
+            "swap_debt",
+            json!({
+                "account_id": account_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+                "min_amount_out": U128(min_amount_out),
+                "token_out": token_out,
+            }),
+        );
+    }
+
+    pub fn swap_deleverage(
+        account_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+        min_amount_out: Balance,
+        token_out: &TokenId,
+    ) {
+        log_event(
+            "swap_deleverage",
+            json!({
+                "account_id": account_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+                "min_amount_out": U128(min_amount_out),
+                "token_out": token_out,
+            }),
+        );
+    }
+
+    pub fn deleverage(
+        account_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+        amount_out: Balance,
+        token_out: &TokenId,
+    ) {
+        log_event(
+            "deleverage",
+            json!({
+                "account_id": account_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+                "amount_out": U128(amount_out),
+                "token_out": token_out,
+            }),
+        );
+    }
+
+    pub fn order_placed(account_id: &AccountId, order_id: OrderId, order: &Order) {
+        log_event(
+            "order_placed",
+            json!({
+                "account_id": account_id,
+                "order_id": order_id,
+                "order": order,
+            }),
+        );
+    }
+
+    pub fn order_cancelled(account_id: &AccountId, order_id: OrderId) {
+        log_event(
+            "order_cancelled",
+            json!({
+                "account_id": account_id,
+                "order_id": order_id,
+            }),
+        );
+    }
+
+    pub fn order_executed(
+        keeper_id: &AccountId,
+        account_id: &AccountId,
+        order_id: OrderId,
+        order: &Order,
+    ) {
+        log_event(
+            "order_executed",
+            json!({
+                "keeper_id": keeper_id,
+                "account_id": account_id,
+                "order_id": order_id,
+                "order": order,
+            }),
+        );
+    }
+
+    pub fn swap_succeeded(
+        account_id: &AccountId,
+        pending_swap: &PendingSwap,
+        refund_amount: Balance,
+    ) {
+        log_event(
+            "swap_succeeded",
+            json!({
+                "account_id": account_id,
+                "kind": pending_swap.kind,
+                "token_in": pending_swap.token_in,
+                "amount_in": U128(pending_swap.amount_in),
+                "token_out": pending_swap.token_out,
+                "amount_out": pending_swap.amount_out,
+                "refund_amount": U128(refund_amount),
+            }),
+        );
+    }
+
+    pub fn swap_output_pending(
+        account_id: &AccountId,
+        pending_swap: &PendingSwap,
+        refund_amount: Balance,
+    ) {
+        log_event(
+            "swap_output_pending",
+            json!({
+                "account_id": account_id,
+                "kind": pending_swap.kind,
+                "token_in": pending_swap.token_in,
+                "amount_in": U128(pending_swap.amount_in),
+                "token_out": pending_swap.token_out,
+                "refund_amount": U128(refund_amount),
+            }),
+        );
+    }
+
+    pub fn swap_failed(account_id: &AccountId, pending_swap: &PendingSwap, refund_amount: Balance) {
+        log_event(
+            "swap_failed",
+            json!({
+                "account_id": account_id,
+                "kind": pending_swap.kind,
+                "token_in": pending_swap.token_in,
+                "amount_in": U128(pending_swap.amount_in),
+                "token_out": pending_swap.token_out,
+                "refund_amount": U128(refund_amount),
+            }),
+        );
+    }
 }
@@ -11,8 +11,14 @@ const GAS_FOR_AFTER_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 20);
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Serialize))]
 #[serde(crate = "near_sdk::serde")]
 pub enum TokenReceiverMsg {
-    Execute { actions: Vec<Action> },
+    Execute {
+        actions: Vec<Action>,
+    },
     DepositToReserve,
+    /// The output of the swap for the given account. Can only be sent by the DEX.
+    SwapOutput {
+        account_id: AccountId,
+    },
 }
 
 #[near_bindgen]
@@ -51,6 +57,15 @@ impl FungibleTokenReceiver for Contract {
                     events::emit::deposit_to_reserve(&sender_id, amount, &token_id);
                     return PromiseOrValue::Value(U128(0));
                 }
+                TokenReceiverMsg::SwapOutput { account_id } => {
+                    assert_eq!(
+                        Some(sender_id),
+                        self.internal_config().dex_account_id,
+                        "The swap output can only be sent by the DEX"
+                    );
+                    self.internal_complete_swap(&account_id, &token_id, amount);
+                    return PromiseOrValue::Value(U128(0));
+                }
             }
         };
 
@@ -0,0 +1,336 @@
+use crate::*;
+
+pub type ProposalId = u64;
+
+/// The parameters of the governance by the xBooster holders.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct GovernanceConfig {
+    /// The minimum effective xBooster amount of an account to create a proposal.
+    #[serde(with = "u128_dec_format")]
+    pub min_proposal_x_booster_amount: Balance,
+    /// The duration in seconds of the voting on a proposal since it's created.
+    pub voting_duration_sec: DurationSec,
+    /// The duration in seconds after the voting ends before the approved proposal can be executed.
+    pub timelock_duration_sec: DurationSec,
+    /// The minimum total xBooster amount that voted for the proposal to approve it.
+    #[serde(with = "u128_dec_format")]
+    pub quorum_x_booster_amount: Balance,
+}
+
+/// The privileged call that is executed once the proposal is approved.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalAction {
+    UpdateConfig {
+        config: Config,
+    },
+    UpdateAsset {
+        token_id: TokenId,
+        asset_config: AssetConfig,
+    },
+    AddAssetFarmReward {
+        farm_id: FarmId,
+        reward_token_id: TokenId,
+        new_reward_per_day: U128,
+        new_booster_log_base: U128,
+        reward_amount: U128,
+        schedule: Option<FarmRewardSchedule>,
+        boost_curve: Option<BoostCurve>,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    pub proposer_id: AccountId,
+    pub description: String,
+    pub action: ProposalAction,
+    /// The total effective xBooster amount of the accounts that voted for the proposal.
+    #[serde(with = "u128_dec_format")]
+    pub votes_for: Balance,
+    /// The total effective xBooster amount of the accounts that voted against the proposal.
+    #[serde(with = "u128_dec_format")]
+    pub votes_against: Balance,
+    /// When the voting ends in nanoseconds.
+    #[serde(with = "u64_dec_format")]
+    pub voting_end_timestamp: Timestamp,
+    /// When the approved proposal can be executed in nanoseconds.
+    #[serde(with = "u64_dec_format")]
+    pub execution_timestamp: Timestamp,
+    pub executed: bool,
+    /// Whether the proposal was cancelled by a guardian.
+    pub cancelled: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum VProposal {
+    Current(Proposal),
+}
+
+impl From<VProposal> for Proposal {
+    fn from(v: VProposal) -> Self {
+        match v {
+            VProposal::Current(c) => c,
+        }
+    }
+}
+
+impl From<Proposal> for VProposal {
+    fn from(c: Proposal) -> Self {
+        VProposal::Current(c)
+    }
+}
+
+/// The vote of an account on a proposal.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalVote {
+    pub approve: bool,
+    /// The effective xBooster amount of the account at the moment of voting.
+    #[serde(with = "u128_dec_format")]
+    pub voting_power: Balance,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalView {
+    pub proposal_id: ProposalId,
+    #[serde(flatten)]
+    pub proposal: Proposal,
+}
+
+impl Proposal {
+    pub fn is_approved(&self, governance: &GovernanceConfig) -> bool {
+        self.votes_for >= governance.quorum_x_booster_amount && self.votes_for > self.votes_against
+    }
+}
+
+impl Contract {
+    pub fn internal_unwrap_proposal(&self, proposal_id: ProposalId) -> Proposal {
+        self.proposals
+            .get(proposal_id)
+            .expect("Proposal not found")
+            .into()
+    }
+
+    /// Returns the effective xBooster amount of the account that is used as its voting power.
+    fn internal_voting_power(&self, account: &Account, config: &Config) -> Balance {
+        account
+            .booster_staking
+            .as_ref()
+            .map(|b| b.get_x_booster_amount(config, env::block_timestamp()))
+            .unwrap_or(0)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a new proposal with the given privileged action. The voting starts immediately.
+    /// The storage of the proposal is paid by the proposer.
+    /// - Panics if the governance is disabled.
+    /// - Panics if the effective xBooster amount of the proposer is less than required.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn create_proposal(&mut self, description: String, action: ProposalAction) -> ProposalId {
+        assert_one_yocto();
+        let config = self.internal_config();
+        let governance = config
+            .governance
+            .as_ref()
+            .expect("The governance is disabled");
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        assert!(
+            self.internal_voting_power(&account, &config)
+                >= governance.min_proposal_x_booster_amount,
+            "Not enough xBooster to create a proposal"
+        );
+
+        let voting_end_timestamp =
+            env::block_timestamp() + sec_to_nano(governance.voting_duration_sec);
+        // The guardians have at least the owner action delay to cancel the approved proposal.
+        let timelock_duration_sec = std::cmp::max(
+            governance.timelock_duration_sec,
+            config.owner_action_delay_sec,
+        );
+        let proposal = Proposal {
+            proposer_id: account_id.clone(),
+            description,
+            action,
+            votes_for: 0,
+            votes_against: 0,
+            voting_end_timestamp,
+            execution_timestamp: voting_end_timestamp + sec_to_nano(timelock_duration_sec),
+            executed: false,
+            cancelled: false,
+        };
+        let proposal_id = self.proposals.len();
+        account.storage_tracker.start();
+        self.proposals.push(&proposal.into());
+        account.storage_tracker.stop();
+        self.internal_set_account(&account_id, account);
+
+        events::emit::proposal_created(&account_id, proposal_id);
+        proposal_id
+    }
+
+    /// Votes for or against the given proposal with the current effective xBooster amount of the
+    /// account. Each account can only vote once. The storage of the vote is paid by the voter.
+    /// The staked booster token of the account can't be unstaked until the voting ends.
+    /// - Panics if the voting has ended.
+    /// - Panics if the account has already voted or doesn't have xBooster.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn vote_proposal(&mut self, proposal_id: ProposalId, approve: bool) {
+        assert_one_yocto();
+        let config = self.internal_config();
+        let mut proposal = self.internal_unwrap_proposal(proposal_id);
+        assert!(
+            env::block_timestamp() < proposal.voting_end_timestamp,
+            "The voting has ended"
+        );
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        let voting_power = self.internal_voting_power(&account, &config);
+        assert!(voting_power > 0, "No xBooster to vote");
+
+        let key = (proposal_id, account_id.clone());
+        assert!(
+            !self.proposal_votes.contains_key(&key),
+            "Already voted on the proposal"
+        );
+        account.storage_tracker.start();
+        self.proposal_votes.insert(
+            &key,
+            &ProposalVote {
+                approve,
+                voting_power,
+            },
+        );
+        account.storage_tracker.stop();
+        account.booster_vote_lock_timestamp = std::cmp::max(
+            account.booster_vote_lock_timestamp,
+            proposal.voting_end_timestamp,
+        );
+        self.internal_set_account(&account_id, account);
+
+        if approve {
+            proposal.votes_for += voting_power;
+        } else {
+            proposal.votes_against += voting_power;
+        }
+        self.proposals.replace(proposal_id, &proposal.into());
+
+        events::emit::proposal_vote(&account_id, proposal_id, approve, voting_power);
+    }
+
+    /// Executes the action of the approved proposal. Can be called by anyone.
+    /// - Panics if the governance is disabled.
+    /// - Panics if the proposal is already executed or cancelled.
+    /// - Panics if the timelock has not passed since the end of the voting.
+    /// - Panics if the proposal didn't reach the quorum or has more votes against.
+    pub fn execute_proposal(&mut self, proposal_id: ProposalId) {
+        let config = self.internal_config();
+        let governance = config.governance.expect("The governance is disabled");
+        let mut proposal = self.internal_unwrap_proposal(proposal_id);
+        assert!(!proposal.executed, "The proposal is already executed");
+        assert!(!proposal.cancelled, "The proposal is cancelled");
+        assert!(
+            env::block_timestamp() >= proposal.execution_timestamp,
+            "The proposal can't be executed yet"
+        );
+        assert!(
+            proposal.is_approved(&governance),
+            "The proposal is not approved"
+        );
+        proposal.executed = true;
+        let action = proposal.action.clone();
+        self.proposals.replace(proposal_id, &proposal.into());
+
+        match action {
+            ProposalAction::UpdateConfig { config } => {
+                self.internal_update_config(config);
+            }
+            ProposalAction::UpdateAsset {
+                token_id,
+                asset_config,
+            } => {
+                self.internal_update_asset(&token_id, asset_config);
+            }
+            ProposalAction::AddAssetFarmReward {
+                farm_id,
+                reward_token_id,
+                new_reward_per_day,
+                new_booster_log_base,
+                reward_amount,
+                schedule,
+                boost_curve,
+            } => {
+                self.internal_add_asset_farm_reward(
+                    farm_id,
+                    reward_token_id,
+                    new_reward_per_day.0,
+                    new_booster_log_base.0,
+                    reward_amount.0,
+                    schedule,
+                    boost_curve,
+                );
+            }
+        }
+
+        events::emit::proposal_executed(proposal_id);
+    }
+
+    /// Cancels the proposal, so it can't be executed even if it's approved.
+    /// - Panics if the proposal is already executed or cancelled.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the guardian role.
+    #[payable]
+    pub fn cancel_proposal(&mut self, proposal_id: ProposalId) {
+        assert_one_yocto();
+        self.assert_role(Role::Guardian);
+        let mut proposal = self.internal_unwrap_proposal(proposal_id);
+        assert!(!proposal.executed, "The proposal is already executed");
+        assert!(!proposal.cancelled, "The proposal is already cancelled");
+        proposal.cancelled = true;
+        self.proposals.replace(proposal_id, &proposal.into());
+
+        events::emit::proposal_cancelled(proposal_id);
+    }
+
+    /// Returns the proposal with the given ID.
+    pub fn get_proposal(&self, proposal_id: ProposalId) -> Option<ProposalView> {
+        self.proposals
+            .get(proposal_id)
+            .map(|proposal| ProposalView {
+                proposal_id,
+                proposal: proposal.into(),
+            })
+    }
+
+    /// Returns a list of proposals starting from the given index.
+    pub fn get_proposals_paged(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<ProposalView> {
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(self.proposals.len());
+        (from_index..std::cmp::min(self.proposals.len(), from_index + limit))
+            .filter_map(|proposal_id| self.get_proposal(proposal_id))
+            .collect()
+    }
+
+    /// Returns the vote of the given account on the given proposal.
+    pub fn get_proposal_vote(
+        &self,
+        proposal_id: ProposalId,
+        account_id: AccountId,
+    ) -> Option<ProposalVote> {
+        self.proposal_votes.get(&(proposal_id, account_id))
+    }
+}
@@ -3,6 +3,9 @@ use crate::*;
 /// Default multiplier for Net TVL farming. Equals to 1.
 const DEFAULT_NET_TVL_MULTIPLIER: u32 = 10000;
 
+/// Default duration of the time-weighted average price. Equals to 30 minutes.
+const DEFAULT_TWAP_DURATION_SEC: DurationSec = 30 * 60;
+
 /// V0 legacy version of Account structure, before staking of the burrow token was introduced.
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct AccountV0 {
@@ -112,6 +115,58 @@ impl AccountV1 {
             affected_farms,
             storage_tracker,
             booster_staking,
+            pending_swap: None,
+            orders: vec![],
+            next_order_id: 0,
+            auto_repay_enabled: false,
+            claimed_farm_rewards: HashMap::new(),
+            booster_vote_lock_timestamp: 0,
+        }
+    }
+}
+
+/// V2 legacy version of Account structure, before swaps through a DEX were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AccountV2 {
+    /// A copy of an account ID. Saves one storage_read when iterating on accounts.
+    pub account_id: AccountId,
+    /// A list of assets that are supplied by the account (but not used a collateral).
+    pub supplied: HashMap<TokenId, Shares>,
+    /// A list of collateral assets.
+    pub collateral: HashMap<TokenId, Shares>,
+    /// A list of borrowed assets.
+    pub borrowed: HashMap<TokenId, Shares>,
+    /// Keeping track of data required for farms for this account.
+    pub farms: HashMap<FarmId, AccountFarm>,
+    /// Staking of booster token.
+    pub booster_staking: Option<BoosterStaking>,
+}
+
+impl From<AccountV2> for Account {
+    fn from(a: AccountV2) -> Self {
+        let AccountV2 {
+            account_id,
+            supplied,
+            collateral,
+            borrowed,
+            farms,
+            booster_staking,
+        } = a;
+        Self {
+            account_id,
+            supplied,
+            collateral,
+            borrowed,
+            farms,
+            affected_farms: Default::default(),
+            storage_tracker: Default::default(),
+            booster_staking,
+            pending_swap: None,
+            orders: vec![],
+            next_order_id: 0,
+            auto_repay_enabled: false,
+            claimed_farm_rewards: HashMap::new(),
+            booster_vote_lock_timestamp: 0,
         }
     }
 }
@@ -180,6 +235,61 @@ impl From<AssetConfigV0> for AssetConfig {
             can_use_as_collateral,
             can_borrow,
             net_tvl_multiplier: DEFAULT_NET_TVL_MULTIPLIER,
+            collateral_price_mode: PriceMode::Spot,
+            debt_price_mode: PriceMode::Spot,
+            derived_price: None,
+            max_price_staleness_sec: None,
+        }
+    }
+}
+
+/// V1 legacy version of AssetConfig structure, before the TWAP price modes were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AssetConfigV1 {
+    pub reserve_ratio: u32,
+    pub target_utilization: u32,
+    pub target_utilization_rate: LowU128,
+    pub max_utilization_rate: LowU128,
+    pub volatility_ratio: u32,
+    pub extra_decimals: u8,
+    pub can_deposit: bool,
+    pub can_withdraw: bool,
+    pub can_use_as_collateral: bool,
+    pub can_borrow: bool,
+    pub net_tvl_multiplier: u32,
+}
+
+impl From<AssetConfigV1> for AssetConfig {
+    fn from(a: AssetConfigV1) -> Self {
+        let AssetConfigV1 {
+            reserve_ratio,
+            target_utilization,
+            target_utilization_rate,
+            max_utilization_rate,
+            volatility_ratio,
+            extra_decimals,
+            can_deposit,
+            can_withdraw,
+            can_use_as_collateral,
+            can_borrow,
+            net_tvl_multiplier,
+        } = a;
+        Self {
+            reserve_ratio,
+            target_utilization,
+            target_utilization_rate,
+            max_utilization_rate,
+            volatility_ratio,
+            extra_decimals,
+            can_deposit,
+            can_withdraw,
+            can_use_as_collateral,
+            can_borrow,
+            net_tvl_multiplier,
+            collateral_price_mode: PriceMode::Spot,
+            debt_price_mode: PriceMode::Spot,
+            derived_price: None,
+            max_price_staleness_sec: None,
         }
     }
 }
@@ -217,3 +327,172 @@ impl From<AssetV0> for Asset {
         }
     }
 }
+
+/// V1 legacy version of Asset structure, before the TWAP price modes were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AssetV1 {
+    pub supplied: Pool,
+    pub borrowed: Pool,
+    pub reserved: Balance,
+    pub last_update_timestamp: Timestamp,
+    pub config: AssetConfigV1,
+}
+
+impl From<AssetV1> for Asset {
+    fn from(a: AssetV1) -> Self {
+        let AssetV1 {
+            supplied,
+            borrowed,
+            reserved,
+            last_update_timestamp,
+            config,
+        } = a;
+        Self {
+            supplied,
+            borrowed,
+            reserved,
+            last_update_timestamp,
+            config: config.into(),
+        }
+    }
+}
+
+/// V0 legacy version of Config structure, before swaps through a DEX were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ConfigV0 {
+    /// The account ID of the oracle contract
+    pub oracle_account_id: AccountId,
+
+    /// The account ID of the contract owner that allows to modify config, assets and use reserves.
+    pub owner_id: AccountId,
+
+    /// The account ID of the booster token contract.
+    pub booster_token_id: TokenId,
+
+    /// The number of decimals of the booster fungible token.
+    pub booster_decimals: u8,
+
+    /// The total number of different assets
+    pub max_num_assets: u32,
+
+    /// The maximum number of seconds expected from the oracle price call.
+    pub maximum_recency_duration_sec: DurationSec,
+
+    /// Maximum staleness duration of the price data timestamp.
+    pub maximum_staleness_duration_sec: DurationSec,
+
+    /// The minimum duration to stake booster token in seconds.
+    pub minimum_staking_duration_sec: DurationSec,
+
+    /// The maximum duration to stake booster token in seconds.
+    pub maximum_staking_duration_sec: DurationSec,
+
+    /// The rate of xBooster for the amount of Booster given for the maximum staking duration.
+    pub x_booster_multiplier_at_maximum_staking_duration: u32,
+
+    /// Whether an account with bad debt can be liquidated using reserves.
+    pub force_closing_enabled: bool,
+}
+
+impl From<ConfigV0> for Config {
+    fn from(a: ConfigV0) -> Self {
+        let ConfigV0 {
+            oracle_account_id,
+            owner_id,
+            booster_token_id,
+            booster_decimals,
+            max_num_assets,
+            maximum_recency_duration_sec,
+            maximum_staleness_duration_sec,
+            minimum_staking_duration_sec,
+            maximum_staking_duration_sec,
+            x_booster_multiplier_at_maximum_staking_duration,
+            force_closing_enabled,
+        } = a;
+        Self {
+            oracle_account_id,
+            owner_id,
+            booster_token_id,
+            booster_decimals,
+            max_num_assets,
+            maximum_recency_duration_sec,
+            maximum_staleness_duration_sec,
+            minimum_staking_duration_sec,
+            maximum_staking_duration_sec,
+            x_booster_multiplier_at_maximum_staking_duration,
+            force_closing_enabled,
+            dex_account_id: None,
+            reserve_deleverage_fee: None,
+            secondary_oracle_account_ids: vec![],
+            max_oracle_price_deviation: MAX_RATIO,
+            min_oracle_sources: 1,
+            max_price_change: MAX_RATIO,
+            price_change_window_sec: 0,
+            twap_duration_sec: DEFAULT_TWAP_DURATION_SEC,
+            price_feed_account_id: None,
+            max_price_feed_confidence: 0,
+            booster_decay_enabled: false,
+            booster_early_unstake_penalty: None,
+            governance: None,
+            owner_action_delay_sec: 0,
+        }
+    }
+}
+
+/// V0 legacy version of AssetFarmReward structure, before the reward schedules were introduced.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct AssetFarmRewardV0 {
+    pub reward_per_day: Balance,
+    pub booster_log_base: Balance,
+    pub remaining_rewards: Balance,
+    pub boosted_shares: Balance,
+    pub reward_per_share: BigDecimal,
+}
+
+impl From<AssetFarmRewardV0> for AssetFarmReward {
+    fn from(a: AssetFarmRewardV0) -> Self {
+        let AssetFarmRewardV0 {
+            reward_per_day,
+            booster_log_base,
+            remaining_rewards,
+            boosted_shares,
+            reward_per_share,
+        } = a;
+        Self {
+            reward_per_day,
+            booster_log_base,
+            remaining_rewards,
+            schedule: FarmRewardSchedule::default(),
+            boost_curve: BoostCurve::Logarithmic,
+            boosted_shares,
+            reward_per_share,
+        }
+    }
+}
+
+/// V0 legacy version of AssetFarm structure, before the reward schedules were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AssetFarmV0 {
+    pub block_timestamp: Timestamp,
+    pub rewards: HashMap<TokenId, AssetFarmRewardV0>,
+    pub inactive_rewards: LookupMap<TokenId, VAssetFarmReward>,
+}
+
+impl From<AssetFarmV0> for AssetFarm {
+    fn from(a: AssetFarmV0) -> Self {
+        let AssetFarmV0 {
+            block_timestamp,
+            rewards,
+            inactive_rewards,
+        } = a;
+        Self {
+            block_timestamp,
+            rewards: rewards
+                .into_iter()
+                .map(|(token_id, reward)| (token_id, reward.into()))
+                .collect(),
+            inactive_rewards,
+            ended_rewards: vec![],
+        }
+    }
+}
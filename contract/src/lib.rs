@@ -7,17 +7,25 @@ mod asset;
 mod asset_config;
 mod asset_farm;
 mod asset_view;
+mod auto_repay;
 mod big_decimal;
 mod booster_staking;
 mod config;
+mod derived_price;
 mod events;
 mod fungible_token;
+mod governance;
 mod legacy;
+mod orders;
 mod pool;
+mod price_feed;
 mod price_receiver;
 mod prices;
+mod roles;
 mod storage;
 mod storage_tracker;
+mod swap;
+mod timelock;
 mod upgrade;
 mod utils;
 
@@ -33,19 +41,25 @@ pub use crate::asset_view::*;
 pub use crate::big_decimal::*;
 pub use crate::booster_staking::*;
 pub use crate::config::*;
+pub use crate::derived_price::*;
 pub use crate::fungible_token::*;
+pub use crate::governance::*;
 pub use crate::legacy::*;
+pub use crate::orders::*;
 pub use crate::pool::*;
 pub use crate::price_receiver::*;
 pub use crate::prices::*;
+pub use crate::roles::*;
 pub use crate::storage::*;
 use crate::storage_tracker::*;
+pub use crate::swap::*;
+pub use crate::timelock::*;
 use crate::utils::*;
 
 use common::*;
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
@@ -68,6 +82,11 @@ enum StorageKey {
     InactiveAssetFarmRewards { farm_id: FarmId },
     AssetIds,
     Config,
+    PriceObservations,
+    Proposals,
+    ProposalVotes,
+    TimelockActions,
+    RoleMembers,
 }
 
 #[near_bindgen]
@@ -81,6 +100,25 @@ pub struct Contract {
     pub config: LazyOption<Config>,
     /// The last recorded price info from the oracle. It's used for Net TVL farm computation.
     pub last_prices: HashMap<TokenId, Price>,
+    /// The block timestamps when the last prices were stored.
+    pub last_price_timestamps: HashMap<TokenId, Timestamp>,
+    /// The recent prices of each token used to compute the time-weighted average price.
+    pub price_observations: LookupMap<TokenId, Vec<PriceObservation>>,
+    /// The last prices committed by each oracle. They are only stored if there are secondary
+    /// oracles.
+    pub oracle_prices: HashMap<AccountId, OraclePrices>,
+    /// The exchange rates of the derivative tokens read from their exchange rate contracts.
+    pub exchange_rates: HashMap<TokenId, ExchangeRate>,
+    /// The governance proposals created by the xBooster holders.
+    pub proposals: Vector<VProposal>,
+    /// The votes of the accounts on the proposals.
+    pub proposal_votes: LookupMap<(ProposalId, AccountId), ProposalVote>,
+    /// The owner actions that are queued until their delay passes.
+    pub timelock_actions: UnorderedMap<TimelockActionId, VTimelockedAction>,
+    /// The ID of the next queued owner action.
+    pub next_timelock_action_id: TimelockActionId,
+    /// The accounts that were granted each role.
+    pub role_members: LookupMap<Role, Vec<AccountId>>,
 }
 
 #[near_bindgen]
@@ -89,7 +127,7 @@ impl Contract {
     #[init]
     pub fn new(config: Config) -> Self {
         config.assert_valid();
-        Self {
+        let mut contract = Self {
             accounts: UnorderedMap::new(StorageKey::Accounts),
             storage: LookupMap::new(StorageKey::Storage),
             assets: LookupMap::new(StorageKey::Assets),
@@ -97,6 +135,17 @@ impl Contract {
             asset_ids: UnorderedSet::new(StorageKey::AssetIds),
             config: LazyOption::new(StorageKey::Config, Some(&config)),
             last_prices: HashMap::new(),
-        }
+            last_price_timestamps: HashMap::new(),
+            price_observations: LookupMap::new(StorageKey::PriceObservations),
+            oracle_prices: HashMap::new(),
+            exchange_rates: HashMap::new(),
+            proposals: Vector::new(StorageKey::Proposals),
+            proposal_votes: LookupMap::new(StorageKey::ProposalVotes),
+            timelock_actions: UnorderedMap::new(StorageKey::TimelockActions),
+            next_timelock_action_id: 0,
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+        };
+        contract.internal_grant_all_roles(&config.owner_id);
+        contract
     }
 }
@@ -0,0 +1,193 @@
+use crate::*;
+use std::cmp::Ordering;
+
+/// The maximum number of active orders per account.
+const MAX_NUM_ORDERS: usize = 10;
+
+pub type OrderId = u32;
+
+/// The condition that allows a keeper to execute the order.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum OrderTrigger {
+    /// The health factor of the account falls below the given ratio. E.g. 10500 means 105%.
+    HealthFactorBelow(u32),
+    /// The oracle price of the token falls below the given price.
+    PriceBelow { token_id: TokenId, price: Price },
+    /// The oracle price of the token rises above the given price.
+    PriceAbove { token_id: TokenId, price: Price },
+}
+
+/// A conditional order that deleverages the position once the trigger condition is met.
+/// The keeper that executes the order receives the tip from the collateral of the input token.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Order {
+    pub trigger: OrderTrigger,
+    /// The collateral token that is used to repay the borrowed `token_out`.
+    pub token_in: TokenId,
+    /// The amount of the collateral to use. If `None`, then the entire collateral of the token
+    /// is used, which closes the position.
+    pub amount_in: Option<U128>,
+    /// The borrowed token that is repaid.
+    pub token_out: TokenId,
+    /// If given, the collateral is swapped through the DEX. Otherwise the reserves are used.
+    pub min_amount_out: Option<U128>,
+    /// The amount of the collateral of the input token that is paid to the keeper.
+    pub keeper_tip: U128,
+}
+
+/// The order stored on the account.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountOrder {
+    pub order_id: OrderId,
+    #[serde(flatten)]
+    pub order: Order,
+}
+
+impl Order {
+    pub fn assert_valid(&self) {
+        assert_ne!(
+            self.token_in, self.token_out,
+            "Can't deleverage the token into itself"
+        );
+        if let OrderTrigger::PriceBelow { price, .. } | OrderTrigger::PriceAbove { price, .. } =
+            &self.trigger
+        {
+            price.assert_valid();
+        }
+    }
+
+    fn is_triggered(&self, health_factor: Option<BigDecimal>, prices: &Prices) -> bool {
+        match &self.trigger {
+            OrderTrigger::HealthFactorBelow(ratio) => health_factor
+                .map(|health_factor| health_factor < BigDecimal::from_ratio(*ratio))
+                .unwrap_or(false),
+            OrderTrigger::PriceBelow { token_id, price } => {
+                compare_prices(prices.get_unwrap(token_id), price) == Ordering::Less
+            }
+            OrderTrigger::PriceAbove { token_id, price } => {
+                compare_prices(prices.get_unwrap(token_id), price) == Ordering::Greater
+            }
+        }
+    }
+}
+
+impl Account {
+    pub fn internal_remove_order(&mut self, order_id: OrderId) -> Order {
+        let index = self
+            .orders
+            .iter()
+            .position(|account_order| account_order.order_id == order_id)
+            .expect("Order not found");
+        self.orders.remove(index).order
+    }
+}
+
+impl Contract {
+    /// Executes the order of the given account if its trigger condition is met by the given
+    /// prices. The keeper tip is moved from the collateral of the account to the supplied balance
+    /// of the keeper before the position is deleveraged.
+    /// - Panics if the account is not healthy after the order is executed.
+    pub fn internal_execute_order(
+        &mut self,
+        keeper_id: &AccountId,
+        account_id: &AccountId,
+        order_id: OrderId,
+        prices: Prices,
+    ) {
+        assert_ne!(keeper_id, account_id, "Can't execute your own order");
+        let mut keeper = self.internal_unwrap_account(keeper_id);
+        let mut account = self.internal_unwrap_account(account_id);
+
+        let order = account.internal_remove_order(order_id);
+        let health_factor = self.compute_health_factor(&account, &prices);
+        assert!(
+            order.is_triggered(health_factor, &prices),
+            "The order is not triggered"
+        );
+
+        if order.keeper_tip.0 > 0 {
+            let asset = self.internal_unwrap_asset(&order.token_in);
+            let shares = asset.supplied.amount_to_shares(order.keeper_tip.0, true);
+            account.decrease_collateral(&order.token_in, shares);
+            account.add_affected_farm(FarmId::Supplied(order.token_in.clone()));
+
+            let mut keeper_asset = keeper.internal_get_asset_or_default(&order.token_in);
+            keeper_asset.deposit_shares(shares);
+            keeper.internal_set_asset(&order.token_in, keeper_asset);
+            keeper.add_affected_farm(FarmId::Supplied(order.token_in.clone()));
+        }
+
+        self.internal_execute(
+            account_id,
+            &mut account,
+            vec![Action::Deleverage {
+                token_in: AssetAmount {
+                    token_id: order.token_in.clone(),
+                    amount: order.amount_in,
+                    max_amount: None,
+                },
+                token_out: order.token_out.clone(),
+                min_amount_out: order.min_amount_out,
+            }],
+            prices.clone(),
+        );
+        self.assert_account_healthy(&account, &prices);
+        self.internal_set_account(account_id, account);
+
+        events::emit::order_executed(keeper_id, account_id, order_id, &order);
+
+        self.internal_account_apply_affected_farms(&mut keeper);
+        self.internal_set_account(keeper_id, keeper);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Places a conditional order that any keeper can execute once its trigger condition is met.
+    /// Returns the ID of the new order.
+    /// - The order is stored on the account and its storage is paid by the account.
+    /// - Panics if the account has the maximum number of orders.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn account_place_order(&mut self, order: Order) -> OrderId {
+        assert_one_yocto();
+        order.assert_valid();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        assert!(
+            account.orders.len() < MAX_NUM_ORDERS,
+            "Too many orders for the account"
+        );
+        let order_id = account.next_order_id;
+        account.next_order_id += 1;
+        events::emit::order_placed(&account_id, order_id, &order);
+        account.orders.push(AccountOrder { order_id, order });
+        self.internal_set_account(&account_id, account);
+        order_id
+    }
+
+    /// Cancels the order with the given ID.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn account_cancel_order(&mut self, order_id: OrderId) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        account.internal_remove_order(order_id);
+        events::emit::order_cancelled(&account_id, order_id);
+        self.internal_set_account(&account_id, account);
+    }
+}
+
+/// Compares two prices that can have different decimals.
+fn compare_prices(a: &Price, b: &Price) -> Ordering {
+    let a_value = U384::from(a.multiplier) * U384::exp10(b.decimals as usize);
+    let b_value = U384::from(b.multiplier) * U384::exp10(a.decimals as usize);
+    a_value.cmp(&b_value)
+}
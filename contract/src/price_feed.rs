@@ -0,0 +1,138 @@
+use crate::*;
+use near_sdk::{serde_json, PromiseResult};
+use std::convert::TryFrom;
+
+const GAS_FOR_GET_FEED_PRICES: Gas = Gas(Gas::ONE_TERA.0 * 10);
+const GAS_FOR_PROMISE: Gas = Gas(Gas::ONE_TERA.0 * 10);
+
+#[ext_contract(ext_price_feed)]
+trait ExtPriceFeed {
+    fn get_prices(&self, asset_ids: Vec<AssetId>) -> Vec<Option<FeedPrice>>;
+}
+
+#[ext_contract(ext_price_feed_self)]
+trait ExtPriceFeedSelf {
+    fn after_get_feed_prices(&mut self, sender_id: AccountId, asset_ids: Vec<AssetId>, msg: String);
+}
+
+impl Contract {
+    /// Converts the prices read from the price feed into the prices with their publish
+    /// timestamps. The price of the asset is skipped if it's published in the future or its
+    /// confidence interval is wider than the maximum price feed confidence.
+    pub fn internal_feed_prices(
+        &self,
+        asset_ids: Vec<AssetId>,
+        feed_prices: Vec<Option<FeedPrice>>,
+    ) -> Prices {
+        assert_eq!(
+            asset_ids.len(),
+            feed_prices.len(),
+            "The number of price feed prices doesn't match the number of assets"
+        );
+        let config = self.internal_config();
+        let timestamp = env::block_timestamp();
+        let mut prices = Prices::new();
+        for (asset_id, feed_price) in asset_ids.into_iter().zip(feed_prices) {
+            let feed_price = match feed_price {
+                Some(feed_price) => feed_price,
+                None => continue,
+            };
+            if feed_price.timestamp > timestamp {
+                log!("The price feed price of {} is in the future", asset_id);
+                continue;
+            }
+            if feed_price.confidence
+                > ratio(
+                    feed_price.price.multiplier,
+                    config.max_price_feed_confidence,
+                )
+            {
+                log!("The price feed price of {} is not confident", asset_id);
+                continue;
+            }
+            feed_price.price.assert_valid();
+            let token_id = AccountId::try_from(asset_id).expect("Asset is not a valid token ID");
+            prices.insert(token_id, feed_price.price, feed_price.timestamp);
+        }
+        prices
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Reads the prices of all assets from the price feed contract and then executes the given
+    /// `PriceReceiverMsg` on behalf of the caller using these prices. It's an alternative to
+    /// calling the oracle contract that pushes the prices.
+    /// - Panics if the price feed is disabled.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn execute_with_price_feed(&mut self, msg: String) -> Promise {
+        assert_one_yocto();
+        let price_feed_account_id = self
+            .internal_config()
+            .price_feed_account_id
+            .expect("The price feed is disabled");
+        let parsed_msg: PriceReceiverMsg =
+            serde_json::from_str(&msg).expect("Can't parse PriceReceiverMsg");
+        assert!(
+            !matches!(parsed_msg, PriceReceiverMsg::CommitPrices),
+            "Can't commit prices from the price feed"
+        );
+        let remaining_gas = env::prepaid_gas() - env::used_gas();
+        assert!(
+            remaining_gas >= GAS_FOR_GET_FEED_PRICES + GAS_FOR_PROMISE,
+            "Not enough gas to read prices from the price feed"
+        );
+
+        let asset_ids: Vec<AssetId> = self
+            .asset_ids
+            .iter()
+            .map(|token_id| token_id.to_string())
+            .collect();
+        ext_price_feed::get_prices(
+            asset_ids.clone(),
+            price_feed_account_id,
+            NO_DEPOSIT,
+            GAS_FOR_GET_FEED_PRICES,
+        )
+        .then(ext_price_feed_self::after_get_feed_prices(
+            env::predecessor_account_id(),
+            asset_ids,
+            msg,
+            env::current_account_id(),
+            NO_DEPOSIT,
+            remaining_gas - GAS_FOR_GET_FEED_PRICES - GAS_FOR_PROMISE,
+        ))
+    }
+
+    /// Callback after the prices were read from the price feed. The prices that passed the
+    /// confidence checks are aggregated with the recent prices committed by the oracles as one
+    /// more source. Executes the message on behalf of the sender using the aggregated prices.
+    #[private]
+    pub fn after_get_feed_prices(
+        &mut self,
+        sender_id: AccountId,
+        asset_ids: Vec<AssetId>,
+        msg: String,
+    ) {
+        let feed_prices: Vec<Option<FeedPrice>> = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice(&value).expect("Can't parse price feed prices")
+            }
+            _ => env::panic_str("Failed to read prices from the price feed"),
+        };
+        let msg = serde_json::from_str(&msg).expect("Can't parse PriceReceiverMsg");
+        let feed_prices = self.internal_feed_prices(asset_ids, feed_prices);
+        let price_feed_account_id = self
+            .internal_config()
+            .price_feed_account_id
+            .expect("The price feed is disabled");
+        let mut prices =
+            self.internal_aggregate_source_prices(&price_feed_account_id, &feed_prices);
+        self.internal_resolve_derived_prices(&mut prices);
+        self.internal_check_price_staleness(&mut prices);
+        self.internal_check_price_changes(&mut prices);
+
+        self.internal_execute_price_receiver_msg(&sender_id, msg, prices);
+    }
+}
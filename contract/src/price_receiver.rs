@@ -5,10 +5,21 @@ use near_sdk::serde_json;
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Serialize))]
 #[serde(crate = "near_sdk::serde")]
 pub enum PriceReceiverMsg {
-    Execute { actions: Vec<Action> },
+    Execute {
+        actions: Vec<Action>,
+    },
+    /// Executes the order of the given account on behalf of the keeper.
+    ExecuteOrder {
+        account_id: AccountId,
+        order_id: OrderId,
+    },
+    /// Only commits the prices of the oracle to be aggregated with the prices of other oracles.
+    CommitPrices,
 }
 
 impl Contract {
+    /// Validates the oracle call price data. The staleness of the prices is checked per asset by
+    /// `internal_check_price_staleness`.
     pub fn validate_price_data(&self, data: &PriceData) {
         let config = self.internal_config();
         assert!(
@@ -20,28 +31,52 @@ impl Contract {
             data.timestamp <= timestamp,
             "Price data timestamp is in the future"
         );
-        assert!(
-            timestamp - data.timestamp <= to_nano(config.maximum_staleness_duration_sec),
-            "Price data timestamp is too stale"
-        );
+    }
+
+    /// Executes the message on behalf of the sender using the given prices.
+    pub fn internal_execute_price_receiver_msg(
+        &mut self,
+        sender_id: &AccountId,
+        msg: PriceReceiverMsg,
+        prices: Prices,
+    ) {
+        match msg {
+            PriceReceiverMsg::Execute { actions } => {
+                let mut account = self.internal_unwrap_account(sender_id);
+                self.internal_execute(sender_id, &mut account, actions, prices);
+                self.internal_set_account(sender_id, account);
+            }
+            PriceReceiverMsg::ExecuteOrder {
+                account_id,
+                order_id,
+            } => {
+                self.internal_execute_order(sender_id, &account_id, order_id, prices);
+            }
+            PriceReceiverMsg::CommitPrices => {}
+        }
     }
 }
 
 #[near_bindgen]
 impl OraclePriceReceiver for Contract {
     /// The method will execute a given list of actions in the msg using the prices from the `data`
-    /// provided by the oracle on behalf of the sender_id.
-    /// - Requires to be called by the oracle account ID.
+    /// provided by the oracle on behalf of the sender_id. The prices are aggregated with the
+    /// prices committed by the other oracles.
+    /// - Requires to be called by one of the oracle account IDs.
     fn oracle_on_call(&mut self, sender_id: AccountId, data: PriceData, msg: String) {
-        assert_eq!(env::predecessor_account_id(), self.get_oracle_account_id());
-
-        let actions = match serde_json::from_str(&msg).expect("Can't parse PriceReceiverMsg") {
-            PriceReceiverMsg::Execute { actions } => actions,
-        };
+        let oracle_id = env::predecessor_account_id();
+        assert!(
+            self.internal_config().is_oracle_account_id(&oracle_id),
+            "Not an oracle"
+        );
 
-        let mut account = self.internal_unwrap_account(&sender_id);
+        let msg = serde_json::from_str(&msg).expect("Can't parse PriceReceiverMsg");
         self.validate_price_data(&data);
-        self.internal_execute(&sender_id, &mut account, actions, data.into());
-        self.internal_set_account(&sender_id, account);
+        let mut prices = self.internal_aggregate_prices(&oracle_id, data);
+        self.internal_resolve_derived_prices(&mut prices);
+        self.internal_check_price_staleness(&mut prices);
+        self.internal_check_price_changes(&mut prices);
+
+        self.internal_execute_price_receiver_msg(&sender_id, msg, prices);
     }
 }
@@ -1,47 +1,457 @@
 use crate::*;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 
+#[derive(Clone)]
 pub struct Prices {
     prices: HashMap<TokenId, Price>,
+    /// The timestamps of the prices.
+    timestamps: HashMap<TokenId, Timestamp>,
+    /// The tokens which prices changed too much from the last stored prices.
+    unstable_token_ids: HashSet<TokenId>,
+    /// The tokens which prices are older than the maximum staleness duration of the asset.
+    stale_token_ids: HashSet<TokenId>,
+}
+
+/// The maximum number of price observations stored per token. The observations are sampled at
+/// most once per `1 / (MAX_NUM_PRICE_OBSERVATIONS - 1)` of the TWAP duration, so the stored
+/// observations always span the whole TWAP window.
+const MAX_NUM_PRICE_OBSERVATIONS: usize = 32;
+
+/// The price of the token recorded at the given block timestamp.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct PriceObservation {
+    pub timestamp: Timestamp,
+    pub price: Price,
+}
+
+/// The prices committed by one of the oracles.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OraclePrices {
+    pub timestamp: Timestamp,
+    pub prices: HashMap<TokenId, Price>,
 }
 
 impl Prices {
     pub fn new() -> Self {
         Self {
             prices: HashMap::new(),
+            timestamps: HashMap::new(),
+            unstable_token_ids: HashSet::new(),
+            stale_token_ids: HashSet::new(),
         }
     }
 
+    /// Adds the price of the token with the given timestamp.
+    pub fn insert(&mut self, token_id: TokenId, price: Price, timestamp: Timestamp) {
+        self.prices.insert(token_id.clone(), price);
+        self.timestamps.insert(token_id, timestamp);
+    }
+
+    /// Returns the price of the token if it's present and not stale.
+    pub fn get(&self, token_id: &TokenId) -> Option<&Price> {
+        self.prices
+            .get(token_id)
+            .filter(|_| !self.stale_token_ids.contains(token_id))
+    }
+
+    /// Returns the price of the token. Panics if the price is missing or stale.
     pub fn get_unwrap(&self, token_id: &TokenId) -> &Price {
-        self.prices.get(token_id).expect("Asset price is missing")
+        let price = self.prices.get(token_id).expect("Asset price is missing");
+        assert!(
+            !self.stale_token_ids.contains(token_id),
+            "The price of {} is too stale",
+            token_id
+        );
+        price
+    }
+
+    /// Panics if the price of the token changed too much from the last stored price.
+    pub fn assert_price_stable(&self, token_id: &TokenId) {
+        assert!(
+            !self.unstable_token_ids.contains(token_id),
+            "The price of {} changed too much",
+            token_id
+        );
+    }
+
+    /// Panics if the price of any collateral or borrowed asset of the account changed too much.
+    pub fn assert_account_prices_stable(&self, account: &Account) {
+        for token_id in account.collateral.keys().chain(account.borrowed.keys()) {
+            self.assert_price_stable(token_id);
+        }
     }
 }
 
 impl From<PriceData> for Prices {
     fn from(data: PriceData) -> Self {
-        Self {
-            prices: data
-                .prices
-                .into_iter()
-                .filter_map(|AssetOptionalPrice { asset_id, price }| {
-                    let token_id =
-                        AccountId::try_from(asset_id).expect("Asset is not a valid token ID");
-                    price.map(|price| (token_id, price))
-                })
-                .collect(),
+        let mut prices = Self::new();
+        for AssetOptionalPrice { asset_id, price } in data.prices {
+            let token_id = AccountId::try_from(asset_id).expect("Asset is not a valid token ID");
+            if let Some(price) = price {
+                prices.insert(token_id, price, data.timestamp);
+            }
         }
+        prices
     }
 }
 
 impl Contract {
+    /// Returns the prices from the oracle call aggregated with the recent prices committed by the
+    /// other oracles. If there are secondary oracles, the prices are committed for the given
+    /// oracle.
+    pub fn internal_aggregate_prices(&mut self, oracle_id: &AccountId, data: PriceData) -> Prices {
+        let timestamp = data.timestamp;
+        let prices = Prices::from(data);
+        if self
+            .internal_config()
+            .secondary_oracle_account_ids
+            .is_empty()
+        {
+            return prices;
+        }
+        let aggregated_prices = self.internal_aggregate_source_prices(oracle_id, &prices);
+        self.oracle_prices.insert(
+            oracle_id.clone(),
+            OraclePrices {
+                timestamp,
+                prices: prices.prices,
+            },
+        );
+        aggregated_prices
+    }
+
+    /// Returns the prices from the given source aggregated with the recent prices committed by
+    /// the oracles other than the source. The price of each asset is the median of the prices
+    /// converted to the decimals of the source price. The asset price is skipped if any of the
+    /// prices deviates from the median by more than the maximum oracle price deviation, or if
+    /// there are fewer fresh prices of the asset than the minimum number of oracle sources.
+    /// The timestamp of the asset price is the oldest timestamp of the prices in the median.
+    /// The prices are returned as is if there are no secondary oracles.
+    pub fn internal_aggregate_source_prices(
+        &self,
+        source_id: &AccountId,
+        prices: &Prices,
+    ) -> Prices {
+        let config = self.internal_config();
+        if config.secondary_oracle_account_ids.is_empty() {
+            return prices.clone();
+        }
+
+        let block_timestamp = env::block_timestamp();
+        let other_oracle_prices: Vec<&OraclePrices> = std::iter::once(&config.oracle_account_id)
+            .chain(config.secondary_oracle_account_ids.iter())
+            .filter(|account_id| *account_id != source_id)
+            .filter_map(|account_id| self.oracle_prices.get(account_id))
+            .filter(|oracle_prices| {
+                block_timestamp.saturating_sub(oracle_prices.timestamp)
+                    <= to_nano(config.maximum_staleness_duration_sec)
+            })
+            .collect();
+
+        let mut aggregated_prices = Prices::new();
+        for (token_id, price) in prices.prices.iter() {
+            let mut timestamp = prices.timestamps[token_id];
+            let mut multipliers: Vec<Balance> = vec![price.multiplier];
+            for oracle_prices in other_oracle_prices.iter() {
+                if let Some(multiplier) = oracle_prices
+                    .prices
+                    .get(token_id)
+                    .and_then(|other_price| multiplier_with_decimals(other_price, price.decimals))
+                {
+                    multipliers.push(multiplier);
+                    timestamp = std::cmp::min(timestamp, oracle_prices.timestamp);
+                }
+            }
+            if multipliers.len() < config.min_oracle_sources as usize {
+                log!("Not enough fresh oracle prices of {}", token_id);
+                continue;
+            }
+            multipliers.sort_unstable();
+            let len = multipliers.len();
+            let median = if len % 2 == 1 {
+                multipliers[len / 2]
+            } else {
+                (multipliers[len / 2 - 1] + multipliers[len / 2]) / 2
+            };
+            let max_deviation = ratio(median, config.max_oracle_price_deviation);
+            if median - multipliers[0] > max_deviation
+                || multipliers[len - 1] - median > max_deviation
+            {
+                log!("The oracle prices of {} deviate too much", token_id);
+                continue;
+            }
+            aggregated_prices.insert(
+                token_id.clone(),
+                Price {
+                    multiplier: median,
+                    decimals: price.decimals,
+                },
+                timestamp,
+            );
+        }
+        aggregated_prices
+    }
+
+    /// Replaces the prices of the derivative tokens with the prices derived from their underlying
+    /// tokens. A token can be derived from another derivative token, so the prices are resolved
+    /// until no more prices can be derived. The price of the derivative token is missing if the
+    /// underlying price or the exchange rate is missing.
+    pub fn internal_resolve_derived_prices(&self, prices: &mut Prices) {
+        let mut derived_prices: Vec<(TokenId, DerivedPrice)> = self
+            .asset_ids
+            .iter()
+            .filter_map(|token_id| {
+                self.internal_unwrap_asset(&token_id)
+                    .config
+                    .derived_price
+                    .map(|derived_price| (token_id, derived_price))
+            })
+            .collect();
+        for (token_id, _) in derived_prices.iter() {
+            prices.prices.remove(token_id);
+            prices.timestamps.remove(token_id);
+        }
+        loop {
+            let num_unresolved = derived_prices.len();
+            derived_prices.retain(|(token_id, derived_price)| {
+                match self.compute_derived_price(token_id, derived_price, prices) {
+                    Some((price, timestamp)) => {
+                        prices.insert(token_id.clone(), price, timestamp);
+                        false
+                    }
+                    None => true,
+                }
+            });
+            if derived_prices.is_empty() || derived_prices.len() == num_unresolved {
+                break;
+            }
+        }
+    }
+
+    /// Returns the price of the derivative token in the decimals of the underlying price with its
+    /// timestamp. The timestamp is the oldest of the underlying price and the exchange rate
+    /// timestamps, so a stale cached exchange rate makes the derived price stale.
+    fn compute_derived_price(
+        &self,
+        token_id: &TokenId,
+        derived_price: &DerivedPrice,
+        prices: &Prices,
+    ) -> Option<(Price, Timestamp)> {
+        let underlying_price = prices.prices.get(&derived_price.underlying_token_id)?;
+        let mut timestamp = *prices.timestamps.get(&derived_price.underlying_token_id)?;
+        let rate = match &derived_price.exchange_rate {
+            ExchangeRateSource::Oracle { rate_asset_id } => {
+                timestamp = std::cmp::min(timestamp, *prices.timestamps.get(rate_asset_id)?);
+                *prices.prices.get(rate_asset_id)?
+            }
+            ExchangeRateSource::Contract { .. } => {
+                let exchange_rate = self.exchange_rates.get(token_id)?;
+                timestamp = std::cmp::min(timestamp, exchange_rate.timestamp);
+                exchange_rate.rate
+            }
+        };
+        let multiplier = U256::from(underlying_price.multiplier) * U256::from(rate.multiplier)
+            / U256::exp10(rate.decimals as usize);
+        if multiplier > U256::from(u128::MAX) {
+            return None;
+        }
+        Some((
+            Price {
+                multiplier: multiplier.as_u128(),
+                decimals: underlying_price.decimals,
+            },
+            timestamp,
+        ))
+    }
+
+    /// Marks the tokens which prices are older than the maximum price staleness duration of the
+    /// asset. If the asset doesn't have the limit, the maximum staleness duration from the config
+    /// is used.
+    pub fn internal_check_price_staleness(&self, prices: &mut Prices) {
+        let config = self.internal_config();
+        let block_timestamp = env::block_timestamp();
+        for (token_id, timestamp) in prices.timestamps.iter() {
+            let maximum_staleness_duration_sec = self
+                .internal_get_asset(token_id)
+                .and_then(|asset| asset.config.max_price_staleness_sec)
+                .unwrap_or(config.maximum_staleness_duration_sec);
+            if block_timestamp.saturating_sub(*timestamp) > to_nano(maximum_staleness_duration_sec)
+            {
+                prices.stale_token_ids.insert(token_id.clone());
+            }
+        }
+    }
+
+    /// Marks the tokens which prices changed from the last stored prices by more than the maximum
+    /// price change within the price change window.
+    pub fn internal_check_price_changes(&self, prices: &mut Prices) {
+        let config = self.internal_config();
+        if config.price_change_window_sec == 0 {
+            return;
+        }
+        let block_timestamp = env::block_timestamp();
+        for (token_id, price) in prices.prices.iter() {
+            let is_recent = self
+                .last_price_timestamps
+                .get(token_id)
+                .map(|timestamp| {
+                    block_timestamp.saturating_sub(*timestamp)
+                        < to_nano(config.price_change_window_sec)
+                })
+                .unwrap_or(false);
+            if !is_recent {
+                continue;
+            }
+            if let Some(last_multiplier) = self
+                .last_prices
+                .get(token_id)
+                .and_then(|last_price| multiplier_with_decimals(last_price, price.decimals))
+            {
+                let max_change = ratio(last_multiplier, config.max_price_change);
+                if price.multiplier > last_multiplier + max_change
+                    || price.multiplier + max_change < last_multiplier
+                {
+                    log!("The price of {} changed too much", token_id);
+                    prices.unstable_token_ids.insert(token_id.clone());
+                }
+            }
+        }
+    }
+
     /// Updates last prices in the contract.
     /// The prices will only be stored if the old price for the token is already present or the
-    /// asset with this token ID exists.
+    /// asset with this token ID exists. The stale prices are not stored. The prices that changed
+    /// too much are not stored either, so the last stored price stays the reference until the
+    /// price is back within the maximum change or the price change window has passed.
     pub fn internal_set_prices(&mut self, prices: &Prices) {
         for (token_id, price) in prices.prices.iter() {
+            if prices.stale_token_ids.contains(token_id)
+                || prices.unstable_token_ids.contains(token_id)
+            {
+                continue;
+            }
             if self.last_prices.contains_key(&token_id) || self.assets.contains_key(&token_id) {
                 self.last_prices.insert(token_id.clone(), price.clone());
+                self.last_price_timestamps
+                    .insert(token_id.clone(), env::block_timestamp());
+                self.internal_record_price_observation(token_id, price);
             }
         }
     }
+
+    /// Records the price observation of the token. The price is only sampled once the sampling
+    /// interval has passed since the last observation and the observations that are no longer
+    /// needed for the TWAP are removed.
+    fn internal_record_price_observation(&mut self, token_id: &TokenId, price: &Price) {
+        let timestamp = env::block_timestamp();
+        let twap_duration = to_nano(self.internal_config().twap_duration_sec);
+        let twap_start = timestamp.saturating_sub(twap_duration);
+        let sampling_interval = twap_duration / (MAX_NUM_PRICE_OBSERVATIONS as u64 - 1);
+        let mut observations = self.price_observations.get(token_id).unwrap_or_default();
+        if observations
+            .last()
+            .map(|observation| {
+                timestamp.saturating_sub(observation.timestamp) < sampling_interval
+                    || observation.timestamp == timestamp
+            })
+            .unwrap_or(false)
+        {
+            return;
+        }
+        observations.push(PriceObservation {
+            timestamp,
+            price: *price,
+        });
+        while observations.len() > MAX_NUM_PRICE_OBSERVATIONS
+            || (observations.len() >= 2 && observations[1].timestamp <= twap_start)
+        {
+            observations.remove(0);
+        }
+        self.price_observations.insert(token_id, &observations);
+    }
+
+    /// Returns the time-weighted average price of the token in the decimals of the given spot
+    /// price. Each recorded price lasts until the next observation. Returns `None` if the
+    /// recorded prices don't cover the whole TWAP window.
+    pub fn compute_twap(&self, token_id: &TokenId, spot_price: &Price) -> Option<Price> {
+        let observations = self.price_observations.get(token_id)?;
+        let timestamp = env::block_timestamp();
+        let twap_start =
+            timestamp.saturating_sub(to_nano(self.internal_config().twap_duration_sec));
+        if observations.first()?.timestamp > twap_start {
+            return None;
+        }
+        let mut weighted_sum = U256::zero();
+        let mut total_duration: u64 = 0;
+        for (i, observation) in observations.iter().enumerate() {
+            let start = std::cmp::max(observation.timestamp, twap_start);
+            let end = observations
+                .get(i + 1)
+                .map(|next_observation| next_observation.timestamp)
+                .unwrap_or(timestamp);
+            if end <= start {
+                continue;
+            }
+            let multiplier = multiplier_with_decimals(&observation.price, spot_price.decimals)?;
+            weighted_sum += U256::from(multiplier) * U256::from(end - start);
+            total_duration += end - start;
+        }
+        if total_duration == 0 {
+            return None;
+        }
+        Some(Price {
+            multiplier: (weighted_sum / U256::from(total_duration)).as_u128(),
+            decimals: spot_price.decimals,
+        })
+    }
+
+    /// Returns the price to value the asset according to the price mode of the asset config.
+    /// If there is no TWAP or the TWAP window is not covered yet, then the spot price is used.
+    pub fn get_valuation_price(
+        &self,
+        token_id: &TokenId,
+        prices: &Prices,
+        price_mode: PriceMode,
+        is_collateral: bool,
+    ) -> Price {
+        let spot_price = *prices.get_unwrap(token_id);
+        if price_mode == PriceMode::Spot {
+            return spot_price;
+        }
+        let twap = match self.compute_twap(token_id, &spot_price) {
+            Some(twap) => twap,
+            None => return spot_price,
+        };
+        match price_mode {
+            PriceMode::Spot => spot_price,
+            PriceMode::Twap => twap,
+            PriceMode::Conservative => {
+                let use_twap = if is_collateral {
+                    twap.multiplier < spot_price.multiplier
+                } else {
+                    twap.multiplier > spot_price.multiplier
+                };
+                if use_twap {
+                    twap
+                } else {
+                    spot_price
+                }
+            }
+        }
+    }
+}
+
+/// Returns the multiplier of the price converted to the given number of decimals, or `None` if
+/// the conversion overflows.
+fn multiplier_with_decimals(price: &Price, decimals: u8) -> Option<Balance> {
+    if price.decimals <= decimals {
+        10u128
+            .checked_pow((decimals - price.decimals) as u32)
+            .and_then(|factor| price.multiplier.checked_mul(factor))
+    } else {
+        10u128
+            .checked_pow((price.decimals - decimals) as u32)
+            .map(|factor| price.multiplier / factor)
+    }
 }
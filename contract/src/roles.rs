@@ -0,0 +1,145 @@
+use crate::*;
+
+/// The role that gives access to a group of privileged methods. The contract owner is granted all
+/// roles when the contract is initialized or migrated.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Updates the config and grants or revokes roles.
+    Admin,
+    /// Adds assets and updates their configs.
+    RiskManager,
+    /// Adds, updates and cancels farm rewards.
+    FarmManager,
+    /// Disables borrowing of assets and cancels the queued owner actions and proposals.
+    Guardian,
+    /// Upgrades the contract.
+    Upgrader,
+}
+
+pub const ALL_ROLES: [Role; 5] = [
+    Role::Admin,
+    Role::RiskManager,
+    Role::FarmManager,
+    Role::Guardian,
+    Role::Upgrader,
+];
+
+impl Contract {
+    pub fn internal_get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.role_members.get(&role).unwrap_or_default()
+    }
+
+    /// Whether the given account has the given role.
+    pub fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.internal_get_role_members(role).contains(account_id)
+    }
+
+    pub fn assert_role(&self, role: Role) {
+        assert!(
+            self.has_role(&env::predecessor_account_id(), role),
+            "Missing the required role"
+        );
+    }
+
+    /// Grants all roles to the given account, e.g. to the owner of a new contract.
+    pub fn internal_grant_all_roles(&mut self, account_id: &AccountId) {
+        for role in ALL_ROLES.iter() {
+            if !self.has_role(account_id, *role) {
+                self.internal_grant_role(account_id, *role);
+            }
+        }
+    }
+
+    pub fn internal_grant_role(&mut self, account_id: &AccountId, role: Role) {
+        let mut members = self.internal_get_role_members(role);
+        assert!(
+            !members.contains(account_id),
+            "The account already has the role"
+        );
+        members.push(account_id.clone());
+        self.role_members.insert(&role, &members);
+        events::emit::role_granted(account_id, role);
+    }
+
+    pub fn internal_revoke_role(&mut self, account_id: &AccountId, role: Role) {
+        let mut members = self.internal_get_role_members(role);
+        let index = members
+            .iter()
+            .position(|member_id| member_id == account_id)
+            .expect("The account doesn't have the role");
+        assert!(
+            role != Role::Admin || members.len() > 1,
+            "Can't revoke the last admin"
+        );
+        members.swap_remove(index);
+        if members.is_empty() {
+            self.role_members.remove(&role);
+        } else {
+            self.role_members.insert(&role, &members);
+        }
+        events::emit::role_revoked(account_id, role);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants the given role to the given account. Granting the admin or the upgrader role is an
+    /// owner action that is queued if the owner actions are timelocked.
+    /// - Panics if the account already has the role.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the admin role.
+    #[payable]
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+        assert!(
+            !self.has_role(&account_id, role),
+            "The account already has the role"
+        );
+        if matches!(role, Role::Admin | Role::Upgrader) {
+            self.internal_owner_action(TimelockAction::GrantRole { account_id, role });
+        } else {
+            self.internal_grant_role(&account_id, role);
+        }
+    }
+
+    /// Revokes the given role from the given account. Revoking the guardian role is an owner
+    /// action that is queued if the owner actions are timelocked, so the guardians can't be
+    /// removed before they can cancel the queued actions.
+    /// - Panics if the account doesn't have the role.
+    /// - Panics if the account is the last admin.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the admin role.
+    #[payable]
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+        assert!(
+            self.has_role(&account_id, role),
+            "The account doesn't have the role"
+        );
+        if role == Role::Guardian {
+            self.internal_owner_action(TimelockAction::RevokeRole { account_id, role });
+        } else {
+            self.internal_revoke_role(&account_id, role);
+        }
+    }
+
+    /// Returns the accounts that have the given role.
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.internal_get_role_members(role)
+    }
+
+    /// Returns the roles granted to the given account.
+    pub fn get_account_roles(&self, account_id: AccountId) -> Vec<Role> {
+        ALL_ROLES
+            .iter()
+            .filter(|role| self.internal_get_role_members(**role).contains(&account_id))
+            .cloned()
+            .collect()
+    }
+}
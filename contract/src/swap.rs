@@ -0,0 +1,533 @@
+use crate::*;
+use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
+use near_sdk::serde_json::json;
+use near_sdk::{serde_json, PromiseResult};
+
+const GAS_FOR_SWAP: Gas = Gas(Gas::ONE_TERA.0 * 120);
+const GAS_FOR_AFTER_SWAP: Gas = Gas(Gas::ONE_TERA.0 * 25);
+
+/// Defines where the input tokens of the swap come from and where the output tokens go.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub enum SwapKind {
+    /// The input is taken from the collateral and the output is added to the collateral.
+    Collateral,
+    /// The input is borrowed and the output is used to repay the borrowed output token.
+    Debt,
+    /// The input is taken from the collateral and the output is used to repay the borrowed output
+    /// token.
+    Deleverage,
+}
+
+impl SwapKind {
+    /// Whether the input tokens are taken from the collateral.
+    pub fn takes_collateral(&self) -> bool {
+        matches!(self, SwapKind::Collateral | SwapKind::Deleverage)
+    }
+
+    /// Whether the output tokens are used to repay the borrowed balance.
+    pub fn repays_debt(&self) -> bool {
+        matches!(self, SwapKind::Debt | SwapKind::Deleverage)
+    }
+}
+
+/// A swap through the DEX that was started, but the output tokens were not received yet.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingSwap {
+    pub kind: SwapKind,
+    /// The token that was sent to the DEX.
+    pub token_in: TokenId,
+    /// The amount of the input token that was sent to the DEX.
+    #[serde(with = "u128_dec_format")]
+    pub amount_in: Balance,
+    /// The token that is expected back from the DEX.
+    pub token_out: TokenId,
+    /// The minimum amount of the output token that the DEX has to return.
+    #[serde(with = "u128_dec_format")]
+    pub min_amount_out: Balance,
+    /// The amount of the output token received from the DEX. The swap stays pending until the
+    /// callback for the input token transfer is resolved.
+    pub amount_out: Option<U128>,
+    /// Whether the callback for the input token transfer was resolved. The swap stays pending
+    /// after that only if the DEX used the input tokens, but didn't send the output tokens yet.
+    pub input_resolved: bool,
+}
+
+impl PendingSwap {
+    /// Whether the output tokens are not received yet.
+    pub fn is_waiting_for_output(&self) -> bool {
+        self.amount_out.is_none()
+    }
+}
+
+impl Account {
+    /// Returns the minimum amount of the borrowed token that is going to be repaid by the pending
+    /// swap.
+    pub fn get_pending_swap_repayment(&self, token_id: &TokenId) -> Balance {
+        self.pending_swap
+            .as_ref()
+            .filter(|pending_swap| {
+                pending_swap.kind.repays_debt()
+                    && pending_swap.is_waiting_for_output()
+                    && &pending_swap.token_out == token_id
+            })
+            .map(|pending_swap| pending_swap.min_amount_out)
+            .unwrap_or(0)
+    }
+}
+
+impl Contract {
+    /// Takes the given amount of collateral from the account and sends it to the DEX to be swapped
+    /// into `token_out`. The swapped tokens will be added to the account collateral once the DEX
+    /// transfers them back.
+    pub fn internal_swap_collateral(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+        token_out: &TokenId,
+        min_amount_out: Balance,
+    ) -> Balance {
+        let asset_out = self.internal_unwrap_asset(token_out);
+        assert!(
+            asset_out.config.can_deposit && asset_out.config.can_use_as_collateral,
+            "The output asset can't be used as a collateral"
+        );
+
+        let amount = self.internal_take_collateral(account, asset_amount);
+
+        self.internal_start_swap(
+            account_id,
+            account,
+            PendingSwap {
+                kind: SwapKind::Collateral,
+                token_in: asset_amount.token_id.clone(),
+                amount_in: amount,
+                token_out: token_out.clone(),
+                min_amount_out,
+                amount_out: None,
+                input_resolved: false,
+            },
+        );
+
+        amount
+    }
+
+    /// Borrows the given amount and sends it to the DEX to be swapped into `token_out`. The swapped
+    /// tokens will be used to repay the borrowed `token_out` once the DEX transfers them back.
+    pub fn internal_swap_debt(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+        token_out: &TokenId,
+        min_amount_out: Balance,
+    ) -> Balance {
+        assert!(
+            account.borrowed.contains_key(token_out),
+            "The output asset is not borrowed"
+        );
+
+        let mut asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        let amount = borrow_asset_amount(&mut asset, account, asset_amount);
+        self.internal_set_asset(&asset_amount.token_id, asset);
+
+        account.add_affected_farm(FarmId::Borrowed(asset_amount.token_id.clone()));
+
+        self.internal_start_swap(
+            account_id,
+            account,
+            PendingSwap {
+                kind: SwapKind::Debt,
+                token_in: asset_amount.token_id.clone(),
+                amount_in: amount,
+                token_out: token_out.clone(),
+                min_amount_out,
+                amount_out: None,
+                input_resolved: false,
+            },
+        );
+
+        amount
+    }
+
+    /// Takes the given amount of collateral from the account and sends it to the DEX to be swapped
+    /// into the borrowed `token_out`. The swapped tokens will be used to repay the borrowed
+    /// `token_out` once the DEX transfers them back.
+    pub fn internal_swap_deleverage(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+        token_out: &TokenId,
+        min_amount_out: Balance,
+    ) -> Balance {
+        assert!(
+            account.borrowed.contains_key(token_out),
+            "The output asset is not borrowed"
+        );
+
+        let amount = self.internal_take_collateral(account, asset_amount);
+
+        self.internal_start_swap(
+            account_id,
+            account,
+            PendingSwap {
+                kind: SwapKind::Deleverage,
+                token_in: asset_amount.token_id.clone(),
+                amount_in: amount,
+                token_out: token_out.clone(),
+                min_amount_out,
+                amount_out: None,
+                input_resolved: false,
+            },
+        );
+
+        amount
+    }
+
+    /// Takes the given amount of collateral from the account into the reserves and repays the
+    /// borrowed `token_out` using reserves. The repaid amount is computed from the oracle prices
+    /// minus the reserve deleverage fee. The amount that exceeds the borrowed balance stays
+    /// supplied. Returns the amount of collateral taken and the amount of `token_out` repaid.
+    pub fn internal_deleverage_with_reserves(
+        &mut self,
+        account: &mut Account,
+        prices: &Prices,
+        asset_amount: &AssetAmount,
+        token_out: &TokenId,
+    ) -> (Balance, Balance) {
+        let fee = self
+            .internal_config()
+            .reserve_deleverage_fee
+            .expect("Deleveraging using reserves is not enabled");
+        assert!(
+            account.borrowed.contains_key(token_out),
+            "The output asset is not borrowed"
+        );
+        assert_ne!(
+            &asset_amount.token_id, token_out,
+            "Can't deleverage the token into itself"
+        );
+
+        let amount = self.internal_take_collateral(account, asset_amount);
+        let mut asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        asset.reserved += amount;
+        let value = BigDecimal::from_balance_price(
+            amount,
+            prices.get_unwrap(&asset_amount.token_id),
+            asset.config.extra_decimals,
+        )
+        .mul_ratio(MAX_RATIO - fee);
+        self.internal_set_asset(&asset_amount.token_id, asset);
+
+        let mut asset_out = self.internal_unwrap_asset(token_out);
+        let amount_out = value.to_balance_price(
+            prices.get_unwrap(token_out),
+            asset_out.config.extra_decimals,
+        );
+        assert!(amount_out > 0, "The deleverage amount is too small");
+        assert!(
+            asset_out.reserved >= amount_out,
+            "Not enough {} in reserve",
+            token_out
+        );
+        asset_out.reserved -= amount_out;
+        self.internal_set_asset(token_out, asset_out);
+
+        self.internal_deposit_and_repay(account, token_out, amount_out);
+
+        (amount, amount_out)
+    }
+
+    /// Decreases the collateral and withdraws the corresponding amount from the supplied pool.
+    /// Returns the amount that can be transferred out of the contract.
+    fn internal_take_collateral(
+        &mut self,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        let mut asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        let collateral_shares = account.internal_unwrap_collateral(&asset_amount.token_id);
+
+        let (shares, amount) =
+            asset_amount_to_shares(&asset.supplied, collateral_shares, asset_amount, false);
+
+        let available_amount = asset.available_amount();
+        assert!(
+            amount <= available_amount,
+            "Swap error: Exceeded available amount {} of {}",
+            available_amount,
+            &asset_amount.token_id
+        );
+
+        account.decrease_collateral(&asset_amount.token_id, shares);
+        account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+
+        asset.supplied.withdraw(shares, amount);
+        self.internal_set_asset(&asset_amount.token_id, asset);
+
+        amount
+    }
+
+    /// Records the pending swap for the account and sends the input tokens to the DEX.
+    fn internal_start_swap(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        pending_swap: PendingSwap,
+    ) {
+        let dex_account_id = self
+            .internal_config()
+            .dex_account_id
+            .expect("Swaps are not enabled");
+        assert!(
+            account.pending_swap.is_none(),
+            "The account already has a pending swap"
+        );
+        assert_ne!(
+            pending_swap.token_in, pending_swap.token_out,
+            "Can't swap the token into itself"
+        );
+        assert!(
+            pending_swap.min_amount_out > 0,
+            "The minimum output amount can't be 0"
+        );
+
+        self.internal_ft_transfer_call_dex(account_id, &dex_account_id, &pending_swap);
+        account.pending_swap = Some(pending_swap);
+    }
+
+    /// Sends the given amount of the input token to the DEX with the swap message.
+    pub fn internal_ft_transfer_call_dex(
+        &mut self,
+        account_id: &AccountId,
+        dex_account_id: &AccountId,
+        pending_swap: &PendingSwap,
+    ) -> Promise {
+        let token_id = &pending_swap.token_in;
+        let amount = pending_swap.amount_in;
+        let asset = self.internal_unwrap_asset(token_id);
+        let ft_amount = amount / 10u128.pow(asset.config.extra_decimals as u32);
+        assert!(ft_amount > 0, "The swap amount is too small");
+        let asset_out = self.internal_unwrap_asset(&pending_swap.token_out);
+        let ft_min_amount_out = ceil_div_pow10(
+            pending_swap.min_amount_out,
+            asset_out.config.extra_decimals as u32,
+        );
+        let msg = serde_json::to_string(&SwapMsg {
+            token_out: pending_swap.token_out.clone(),
+            min_amount_out: ft_min_amount_out,
+            msg: json!({ "SwapOutput": { "account_id": account_id } }).to_string(),
+        })
+        .unwrap();
+        ext_fungible_token::ft_transfer_call(
+            dex_account_id.clone(),
+            ft_amount.into(),
+            None,
+            msg,
+            token_id.clone(),
+            ONE_YOCTO,
+            GAS_FOR_SWAP,
+        )
+        .then(ext_swap_self::after_swap(
+            account_id.clone(),
+            token_id.clone(),
+            amount.into(),
+            env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_AFTER_SWAP,
+        ))
+    }
+
+    /// Completes the pending swap of the account with the output tokens received from the DEX.
+    pub fn internal_complete_swap(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId,
+        amount: Balance,
+    ) {
+        let mut account = self.internal_unwrap_account(account_id);
+        let pending_swap = account.pending_swap.as_mut().expect("No pending swap");
+        assert!(
+            pending_swap.is_waiting_for_output(),
+            "The swap is already completed"
+        );
+        assert_eq!(
+            &pending_swap.token_out, token_id,
+            "Unexpected swap output token"
+        );
+        assert!(
+            amount >= pending_swap.min_amount_out,
+            "The swap output amount {} is less than the minimum amount {}",
+            amount,
+            pending_swap.min_amount_out
+        );
+        pending_swap.amount_out = Some(amount.into());
+        let repay = pending_swap.kind.repays_debt();
+        if pending_swap.input_resolved {
+            // The output was sent asynchronously, so the swap is completed here.
+            events::emit::swap_succeeded(account_id, pending_swap, 0);
+            account.pending_swap = None;
+        }
+        self.internal_credit_swap_tokens(&mut account, repay, token_id, amount);
+        self.internal_account_apply_affected_farms(&mut account);
+        self.internal_set_account(account_id, account);
+    }
+
+    /// Credits the swapped tokens or the refunded input tokens of the swap to the account.
+    /// If `repay` is true, the tokens repay the borrowed balance of the same token and the
+    /// remaining amount stays supplied. Otherwise the tokens go to the collateral.
+    fn internal_credit_swap_tokens(
+        &mut self,
+        account: &mut Account,
+        repay: bool,
+        token_id: &TokenId,
+        amount: Balance,
+    ) {
+        if repay {
+            self.internal_deposit_and_repay(account, token_id, amount);
+        } else {
+            self.internal_deposit_collateral(account, token_id, amount);
+        }
+    }
+
+    /// Deposits the given amount to the supplied balance of the account and uses it to repay the
+    /// borrowed balance of the same token. The remaining amount stays supplied.
+    fn internal_deposit_and_repay(
+        &mut self,
+        account: &mut Account,
+        token_id: &TokenId,
+        amount: Balance,
+    ) {
+        self.internal_deposit(account, token_id, amount);
+        if account.borrowed.contains_key(token_id) {
+            account.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+            let mut account_asset = account.internal_unwrap_asset(token_id);
+            self.internal_repay(
+                &mut account_asset,
+                account,
+                &AssetAmount {
+                    token_id: token_id.clone(),
+                    amount: None,
+                    max_amount: Some(amount.into()),
+                },
+            );
+            account.internal_set_asset(token_id, account_asset);
+        }
+    }
+
+    /// Returns the value of the collateral that is expected from the pending swap of the account.
+    /// If `skip_missing_price` is true, then the collateral without a price is valued at zero.
+    pub fn compute_pending_swap_collateral(
+        &self,
+        account: &Account,
+        prices: &Prices,
+        skip_missing_price: bool,
+    ) -> BigDecimal {
+        if let Some(pending_swap) = account.pending_swap.as_ref().filter(|pending_swap| {
+            !pending_swap.kind.repays_debt()
+                && pending_swap.is_waiting_for_output()
+                && !(skip_missing_price && prices.get(&pending_swap.token_out).is_none())
+        }) {
+            let asset = self.internal_unwrap_asset(&pending_swap.token_out);
+            BigDecimal::from_balance_price(
+                pending_swap.min_amount_out,
+                prices.get_unwrap(&pending_swap.token_out),
+                asset.config.extra_decimals,
+            )
+            .mul_ratio(asset.config.volatility_ratio)
+        } else {
+            BigDecimal::zero()
+        }
+    }
+}
+
+fn ceil_div_pow10(amount: Balance, decimals: u32) -> Balance {
+    let divisor = 10u128.pow(decimals);
+    let result = amount / divisor;
+    if result * divisor < amount {
+        result + 1
+    } else {
+        result
+    }
+}
+
+#[ext_contract(ext_swap_self)]
+trait ExtSwapSelf {
+    fn after_swap(&mut self, account_id: AccountId, token_id: TokenId, amount: U128) -> bool;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Callback after the input tokens were sent to the DEX. Returns the unused amount of the input
+    /// tokens back to where they were taken from and clears the pending swap. The swap is failed
+    /// if the DEX didn't use the input tokens. If the DEX used the input tokens, but didn't return
+    /// the output tokens yet, the swap stays pending until the output tokens are received.
+    #[private]
+    pub fn after_swap(&mut self, account_id: AccountId, token_id: TokenId, amount: U128) -> bool {
+        let asset = self.internal_unwrap_asset(&token_id);
+        let decimals = 10u128.pow(asset.config.extra_decimals as u32);
+        let ft_amount = amount.0 / decimals;
+        let used_ft_amount = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(|v| std::cmp::min(v.0, ft_amount))
+                .unwrap_or(ft_amount),
+            _ => 0,
+        };
+        let refund_amount = if used_ft_amount == 0 {
+            amount.0
+        } else {
+            (ft_amount - used_ft_amount) * decimals
+        };
+
+        let mut account = self.internal_unwrap_account(&account_id);
+        let mut pending_swap = account.pending_swap.take().expect("No pending swap");
+        if refund_amount > 0 {
+            self.internal_credit_swap_tokens(
+                &mut account,
+                !pending_swap.kind.takes_collateral(),
+                &token_id,
+                refund_amount,
+            );
+        }
+        let swap_succeeded = !pending_swap.is_waiting_for_output();
+        if swap_succeeded {
+            events::emit::swap_succeeded(&account_id, &pending_swap, refund_amount);
+        } else if used_ft_amount > 0 {
+            // The DEX used the input tokens, but is going to send the output tokens
+            // asynchronously. The swap stays pending until the output tokens are received.
+            pending_swap.amount_in -= refund_amount;
+            pending_swap.input_resolved = true;
+            events::emit::swap_output_pending(&account_id, &pending_swap, refund_amount);
+            account.pending_swap = Some(pending_swap);
+        } else {
+            events::emit::swap_failed(&account_id, &pending_swap, refund_amount);
+        }
+        self.internal_account_apply_affected_farms(&mut account);
+        self.internal_set_account(&account_id, account);
+        swap_succeeded
+    }
+    /// Cancels the pending swap of the given account that is waiting for the output tokens after
+    /// the DEX used the input tokens. It unblocks the account when the DEX fails to send the output
+    /// tokens. The input tokens are not refunded, since they were already used by the DEX.
+    /// - Panics if the account doesn't have a pending swap waiting for asynchronous output.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the guardian role.
+    #[payable]
+    pub fn cancel_pending_swap(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_role(Role::Guardian);
+        let mut account = self.internal_unwrap_account(&account_id);
+        let pending_swap = account.pending_swap.take().expect("No pending swap");
+        assert!(
+            pending_swap.input_resolved,
+            "The swap input transfer is not resolved yet"
+        );
+        events::emit::swap_failed(&account_id, &pending_swap, 0);
+        self.internal_set_account(&account_id, account);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::*;
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::CryptoHash;
+
+pub type TimelockActionId = u64;
+
+/// The privileged owner call that is queued when the owner actions are timelocked.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TimelockAction {
+    UpdateConfig {
+        config: Config,
+    },
+    AddAsset {
+        token_id: TokenId,
+        asset_config: AssetConfig,
+    },
+    UpdateAsset {
+        token_id: TokenId,
+        asset_config: AssetConfig,
+    },
+    /// Enables borrowing of the asset. Disabling borrowing is an emergency pause that is not
+    /// timelocked.
+    EnableAssetBorrowing {
+        token_id: TokenId,
+    },
+    /// Grants the admin or the upgrader role.
+    GrantRole {
+        account_id: AccountId,
+        role: Role,
+    },
+    /// Revokes the guardian role.
+    RevokeRole {
+        account_id: AccountId,
+        role: Role,
+    },
+    /// The upgrade to the contract code with the given hash. It's executed by calling `upgrade`
+    /// with the code.
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimelockedAction {
+    pub action: TimelockAction,
+    /// When the action can be executed in nanoseconds.
+    #[serde(with = "u64_dec_format")]
+    pub eta: Timestamp,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum VTimelockedAction {
+    Current(TimelockedAction),
+}
+
+impl From<VTimelockedAction> for TimelockedAction {
+    fn from(v: VTimelockedAction) -> Self {
+        match v {
+            VTimelockedAction::Current(c) => c,
+        }
+    }
+}
+
+impl From<TimelockedAction> for VTimelockedAction {
+    fn from(c: TimelockedAction) -> Self {
+        VTimelockedAction::Current(c)
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimelockedActionView {
+    pub action_id: TimelockActionId,
+    #[serde(flatten)]
+    pub timelocked_action: TimelockedAction,
+}
+
+impl TimelockAction {
+    pub fn assert_valid(&self) {
+        match self {
+            TimelockAction::UpdateConfig { config } => config.assert_valid(),
+            TimelockAction::AddAsset { asset_config, .. }
+            | TimelockAction::UpdateAsset { asset_config, .. } => asset_config.assert_valid(),
+            TimelockAction::EnableAssetBorrowing { .. }
+            | TimelockAction::GrantRole { .. }
+            | TimelockAction::RevokeRole { .. }
+            | TimelockAction::Upgrade { .. } => {}
+        }
+    }
+
+    /// Returns the role that is required to queue and execute the action.
+    pub fn role(&self) -> Role {
+        match self {
+            TimelockAction::UpdateConfig { .. }
+            | TimelockAction::GrantRole { .. }
+            | TimelockAction::RevokeRole { .. } => Role::Admin,
+            TimelockAction::AddAsset { .. }
+            | TimelockAction::UpdateAsset { .. }
+            | TimelockAction::EnableAssetBorrowing { .. } => Role::RiskManager,
+            TimelockAction::Upgrade { .. } => Role::Upgrader,
+        }
+    }
+}
+
+impl Contract {
+    /// Executes the owner action immediately if the owner actions are not timelocked. Otherwise
+    /// queues the action to be executed after the delay by an account with the role of the action.
+    pub fn internal_owner_action(&mut self, action: TimelockAction) {
+        action.assert_valid();
+        let delay_sec = self.internal_config().owner_action_delay_sec;
+        if delay_sec == 0 {
+            self.internal_execute_timelock_action(action);
+            return;
+        }
+        let action_id = self.next_timelock_action_id;
+        self.next_timelock_action_id += 1;
+        let eta = env::block_timestamp() + sec_to_nano(delay_sec);
+        self.timelock_actions
+            .insert(&action_id, &TimelockedAction { action, eta }.into());
+        events::emit::timelock_action_queued(action_id, eta);
+    }
+
+    fn internal_execute_timelock_action(&mut self, action: TimelockAction) {
+        match action {
+            TimelockAction::UpdateConfig { config } => {
+                self.internal_update_config(config);
+            }
+            TimelockAction::AddAsset {
+                token_id,
+                asset_config,
+            } => {
+                self.internal_add_asset(&token_id, asset_config);
+            }
+            TimelockAction::UpdateAsset {
+                token_id,
+                asset_config,
+            } => {
+                self.internal_update_asset(&token_id, asset_config);
+            }
+            TimelockAction::EnableAssetBorrowing { token_id } => {
+                let mut asset = self.internal_unwrap_asset(&token_id);
+                asset.config.can_borrow = true;
+                self.internal_set_asset(&token_id, asset);
+            }
+            TimelockAction::GrantRole { account_id, role } => {
+                self.internal_grant_role(&account_id, role);
+            }
+            TimelockAction::RevokeRole { account_id, role } => {
+                self.internal_revoke_role(&account_id, role);
+            }
+            TimelockAction::Upgrade { .. } => {
+                env::panic_str("The upgrade is executed by calling upgrade with the code")
+            }
+        }
+    }
+
+    /// Removes the queued upgrade with the given code hash once its delay has passed.
+    /// - Panics if there is no such upgrade ready to be executed.
+    pub fn internal_take_ready_upgrade(&mut self, code_hash: CryptoHash) {
+        let timestamp = env::block_timestamp();
+        let action_id = self
+            .timelock_actions
+            .iter()
+            .find_map(|(action_id, timelocked_action)| {
+                let timelocked_action: TimelockedAction = timelocked_action.into();
+                match timelocked_action.action {
+                    TimelockAction::Upgrade {
+                        code_hash: queued_code_hash,
+                    } if CryptoHash::from(queued_code_hash) == code_hash
+                        && timelocked_action.eta <= timestamp =>
+                    {
+                        Some(action_id)
+                    }
+                    _ => None,
+                }
+            })
+            .expect("The upgrade is not queued or its delay has not passed");
+        self.timelock_actions.remove(&action_id);
+        events::emit::timelock_action_executed(action_id);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Queues the upgrade to the contract code with the given hash. Once the delay has passed,
+    /// the owner can call `upgrade` with the code.
+    /// - Panics if the owner actions are not timelocked.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the upgrader role.
+    #[payable]
+    pub fn queue_upgrade(&mut self, code_hash: Base58CryptoHash) {
+        assert_one_yocto();
+        self.assert_role(Role::Upgrader);
+        assert!(
+            self.internal_config().owner_action_delay_sec > 0,
+            "The owner actions are not timelocked"
+        );
+        self.internal_owner_action(TimelockAction::Upgrade { code_hash });
+    }
+
+    /// Executes the queued owner action once its delay has passed.
+    /// - Panics if the action is not queued or the delay has not passed.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the role of the action, i.e. the admin role to update the config and grant or
+    ///   revoke roles, and the risk manager role to add or update assets.
+    #[payable]
+    pub fn execute_timelock_action(&mut self, action_id: TimelockActionId) {
+        assert_one_yocto();
+        let timelocked_action: TimelockedAction = self
+            .timelock_actions
+            .remove(&action_id)
+            .expect("Timelock action not found")
+            .into();
+        self.assert_role(timelocked_action.action.role());
+        assert!(
+            timelocked_action.eta <= env::block_timestamp(),
+            "The timelock action delay has not passed"
+        );
+        self.internal_execute_timelock_action(timelocked_action.action);
+        events::emit::timelock_action_executed(action_id);
+    }
+
+    /// Cancels the queued owner action.
+    /// - Panics if the action is not queued.
+    /// - Requires one yoctoNEAR.
+    /// - Requires the guardian role.
+    #[payable]
+    pub fn cancel_timelock_action(&mut self, action_id: TimelockActionId) {
+        assert_one_yocto();
+        self.assert_role(Role::Guardian);
+        self.timelock_actions
+            .remove(&action_id)
+            .expect("Timelock action not found");
+        events::emit::timelock_action_cancelled(action_id);
+    }
+
+    /// Returns all queued owner actions.
+    pub fn get_timelock_actions(&self) -> Vec<TimelockedActionView> {
+        self.timelock_actions
+            .iter()
+            .map(|(action_id, timelocked_action)| TimelockedActionView {
+                action_id,
+                timelocked_action: timelocked_action.into(),
+            })
+            .collect()
+    }
+}
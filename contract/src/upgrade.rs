@@ -8,33 +8,49 @@ impl Contract {
     #[init(ignore_state)]
     pub fn migrate_state() -> Self {
         #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-        pub struct Contract060 {
+        pub struct Contract070 {
             pub accounts: UnorderedMap<AccountId, VAccount>,
             pub storage: LookupMap<AccountId, VStorage>,
             pub assets: LookupMap<TokenId, VAsset>,
             pub asset_farms: LookupMap<FarmId, VAssetFarm>,
             pub asset_ids: UnorderedSet<TokenId>,
-            pub config: LazyOption<Config>,
+            pub config: LazyOption<ConfigV0>,
+            pub last_prices: HashMap<TokenId, Price>,
         }
 
-        let Contract060 {
+        let Contract070 {
             accounts,
             storage,
             assets,
             asset_farms,
             asset_ids,
             config,
+            last_prices,
         } = env::state_read().unwrap();
 
-        Self {
+        let config: Config = config.get().unwrap().into();
+
+        let mut contract = Self {
             accounts,
             storage,
             assets,
             asset_farms,
             asset_ids,
-            config,
-            last_prices: Default::default(),
-        }
+            config: LazyOption::new(StorageKey::Config, Some(&config)),
+            last_prices,
+            last_price_timestamps: Default::default(),
+            price_observations: LookupMap::new(StorageKey::PriceObservations),
+            oracle_prices: Default::default(),
+            exchange_rates: Default::default(),
+            proposals: Vector::new(StorageKey::Proposals),
+            proposal_votes: LookupMap::new(StorageKey::ProposalVotes),
+            timelock_actions: UnorderedMap::new(StorageKey::TimelockActions),
+            next_timelock_action_id: 0,
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+        };
+        // The owner keeps its access to the privileged methods through the explicit roles.
+        contract.internal_grant_all_roles(&config.owner_id);
+        contract
     }
 
     /// Returns semver of this contract.
@@ -48,6 +64,7 @@ mod upgrade {
 
     use super::*;
     use near_sys as sys;
+    use std::convert::TryInto;
 
     const GAS_TO_COMPLETE_UPGRADE_CALL: Gas = Gas(Gas::ONE_TERA.0 * 10);
     const GAS_FOR_GET_CONFIG_CALL: Gas = Gas(Gas::ONE_TERA.0 * 5);
@@ -58,8 +75,15 @@ mod upgrade {
     #[no_mangle]
     pub extern "C" fn upgrade() {
         env::setup_panic_hook();
-        let contract: Contract = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
-        contract.assert_owner();
+        let mut contract: Contract = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
+        contract.assert_role(Role::Upgrader);
+        if contract.internal_config().owner_action_delay_sec > 0 {
+            // The code is only loaded into memory to verify that its upgrade is queued.
+            let code = env::input().expect("ERR_NO_INPUT");
+            let code_hash = env::sha256(&code).try_into().unwrap();
+            contract.internal_take_ready_upgrade(code_hash);
+            env::state_write(&contract);
+        }
         let current_account_id = env::current_account_id().as_bytes().to_vec();
         let migrate_method_name = b"migrate_state".to_vec();
         let get_config_method_name = b"get_config".to_vec();
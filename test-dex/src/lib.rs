@@ -0,0 +1,146 @@
+use common::*;
+
+use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    env, ext_contract, near_bindgen, serde_json, AccountId, Balance, Gas, PromiseOrValue,
+    PromiseResult,
+};
+use std::collections::HashMap;
+
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(Gas::ONE_TERA.0 * 60);
+const GAS_FOR_AFTER_SWAP: Gas = Gas(Gas::ONE_TERA.0 * 10);
+
+#[ext_contract(ext_self)]
+pub trait ExtSelf {
+    fn after_swap(&mut self, amount_in: U128) -> U128;
+}
+
+/// A mock DEX that swaps tokens at fixed rates. The liquidity is provided by transferring tokens
+/// with an empty message.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {
+    /// The rates for pairs `(token_in, token_out)` given as `(numerator, denominator)`.
+    rates: HashMap<(AccountId, AccountId), (Balance, Balance)>,
+    /// Whether the swap outputs are kept until `release_outputs` is called instead of being sent
+    /// right away.
+    delay_outputs: bool,
+    /// The delayed outputs given as `(receiver_id, token_out, amount_out, msg)`.
+    delayed_outputs: Vec<(AccountId, AccountId, Balance, String)>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the rate to swap `token_in` into `token_out`. The output amount is computed as
+    /// `amount_in * numerator / denominator`.
+    pub fn set_rate(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        numerator: U128,
+        denominator: U128,
+    ) {
+        assert!(denominator.0 > 0);
+        self.rates
+            .insert((token_in, token_out), (numerator.0, denominator.0));
+    }
+
+    pub fn get_rate(&self, token_in: AccountId, token_out: AccountId) -> Option<(U128, U128)> {
+        self.rates
+            .get(&(token_in, token_out))
+            .map(|&(numerator, denominator)| (numerator.into(), denominator.into()))
+    }
+
+    /// Sets whether the swap outputs are delayed until `release_outputs` is called.
+    pub fn set_delay_outputs(&mut self, delay_outputs: bool) {
+        self.delay_outputs = delay_outputs;
+    }
+
+    /// Sends all delayed swap outputs without waiting for the results.
+    pub fn release_outputs(&mut self) {
+        for (receiver_id, token_out, amount_out, msg) in self.delayed_outputs.drain(..) {
+            ext_fungible_token::ft_transfer_call(
+                receiver_id,
+                amount_out.into(),
+                None,
+                msg,
+                token_out,
+                ONE_YOCTO,
+                GAS_FOR_FT_TRANSFER_CALL,
+            );
+        }
+    }
+
+    /// Returns the unused amount of the input token. The input is refunded if the output transfer
+    /// was fully refunded.
+    #[private]
+    pub fn after_swap(&mut self, amount_in: U128) -> U128 {
+        let used_amount_out = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(|v| v.0)
+                .unwrap_or(0),
+            _ => 0,
+        };
+        if used_amount_out == 0 {
+            amount_in
+        } else {
+            U128(0)
+        }
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Swaps the received tokens using the `SwapMsg` and sends the output back to the sender.
+    /// The tokens are kept as liquidity if the message is empty.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        if msg.is_empty() {
+            return PromiseOrValue::Value(U128(0));
+        }
+        let token_in = env::predecessor_account_id();
+        let SwapMsg {
+            token_out,
+            min_amount_out,
+            msg,
+        } = serde_json::from_str(&msg).expect("Can't parse SwapMsg");
+
+        let amount_out = self
+            .rates
+            .get(&(token_in, token_out.clone()))
+            .map(|&(numerator, denominator)| amount.0 * numerator / denominator)
+            .unwrap_or(0);
+        if amount_out == 0 || amount_out < min_amount_out {
+            return PromiseOrValue::Value(amount);
+        }
+        if self.delay_outputs {
+            self.delayed_outputs
+                .push((sender_id, token_out, amount_out, msg));
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        ext_fungible_token::ft_transfer_call(
+            sender_id,
+            amount_out.into(),
+            None,
+            msg,
+            token_out,
+            ONE_YOCTO,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .then(ext_self::after_swap(
+            amount,
+            env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_AFTER_SWAP,
+        ))
+        .into()
+    }
+}
@@ -5,6 +5,9 @@ use near_sdk::{assert_one_yocto, env, ext_contract, near_bindgen, AccountId, Gas
 
 const GAS_FOR_PROMISE: Gas = Gas(Gas::ONE_TERA.0 * 10);
 
+/// The message to the receiver to only commit the prices of this oracle.
+const COMMIT_PRICES_MSG: &str = "\"CommitPrices\"";
+
 #[ext_contract(ext_price_receiver)]
 pub trait ExtPriceReceiver {
     fn oracle_on_call(&mut self, sender_id: AccountId, data: PriceData, msg: String);
@@ -37,4 +40,11 @@ impl Contract {
             remaining_gas - GAS_FOR_PROMISE,
         )
     }
+
+    /// Commits the price data to the receiver, so it can be aggregated with the prices of other
+    /// oracles. Multiple instances of this contract can be deployed to act as separate oracles.
+    #[payable]
+    pub fn oracle_commit(&mut self, receiver_id: AccountId, price_data: PriceData) -> Promise {
+        self.oracle_call(receiver_id, price_data, COMMIT_PRICES_MSG.to_string())
+    }
 }
@@ -0,0 +1,50 @@
+use common::*;
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, Balance};
+use std::collections::HashMap;
+
+/// A mock pull oracle that stores the latest price of each asset with its confidence interval.
+/// The prices are published by anyone and read using a view call. It also mocks the exchange rate
+/// of a liquid staking pool.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {
+    prices: HashMap<AssetId, FeedPrice>,
+    exchange_rate: Balance,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Publishes the price of the asset with the given confidence interval at the current block
+    /// timestamp.
+    pub fn set_price(&mut self, asset_id: AssetId, price: Price, confidence: U128) {
+        price.assert_valid();
+        self.prices.insert(
+            asset_id,
+            FeedPrice {
+                price,
+                confidence: confidence.0,
+                timestamp: env::block_timestamp(),
+            },
+        );
+    }
+
+    /// Returns the latest prices of the given assets in the same order.
+    pub fn get_prices(&self, asset_ids: Vec<AssetId>) -> Vec<Option<FeedPrice>> {
+        asset_ids
+            .iter()
+            .map(|asset_id| self.prices.get(asset_id).cloned())
+            .collect()
+    }
+
+    pub fn set_exchange_rate(&mut self, exchange_rate: U128) {
+        self.exchange_rate = exchange_rate.0;
+    }
+
+    /// Returns the exchange rate without arguments, similar to the liquid staking pools.
+    pub fn get_exchange_rate(&self) -> U128 {
+        self.exchange_rate.into()
+    }
+}
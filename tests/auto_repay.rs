@@ -0,0 +1,36 @@
+mod setup;
+
+use crate::setup::*;
+
+#[test]
+fn test_auto_repay() {
+    let (e, tokens, users) = basic_setup();
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(200, 18),
+    )
+    .assert_success();
+
+    e.skip_time(ONE_DAY_SEC);
+
+    assert!(!e.auto_repay(&users.bob, &users.alice, &tokens.ndai).is_ok());
+
+    e.set_auto_repay(&users.alice, true).assert_success();
+    assert!(e.get_account(&users.alice).auto_repay_enabled);
+
+    e.auto_repay(&users.bob, &users.alice, &tokens.ndai)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    let supplied_balance = find_asset(&account.supplied, &tokens.ndai.account_id()).balance;
+    // The borrow interest is larger than the supply interest.
+    assert!(supplied_balance < d(100, 18) && supplied_balance > d(99, 18));
+}
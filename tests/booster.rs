@@ -2,6 +2,7 @@ mod setup;
 
 use crate::setup::*;
 use common::DurationSec;
+use contract::FarmId;
 use near_sdk::serde_json::json;
 
 #[test]
@@ -243,3 +244,212 @@ fn test_booster_stake_all() {
     assert_eq!(booster_staking.staked_booster_amount, amount);
     assert_eq!(booster_staking.x_booster_amount, amount * 4);
 }
+
+#[test]
+fn test_booster_extend_staking() {
+    let (e, tokens, users) = basic_setup();
+
+    e.add_farm(
+        FarmId::BoosterStaking,
+        &tokens.nusdc,
+        d(100, 18),
+        0,
+        d(3000, 18),
+    );
+
+    let amount = d(100, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, amount, "")
+        .assert_success();
+
+    // The staking can't be extended before staking.
+    assert!(!e
+        .account_extend_booster_staking(&users.alice, MAX_DURATION_SEC)
+        .is_ok());
+
+    e.account_stake_booster(&users.alice, amount, MIN_DURATION_SEC)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.booster_staking.unwrap().x_booster_amount, amount);
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, amount);
+
+    e.skip_time(MIN_DURATION_SEC / 2);
+
+    e.account_extend_booster_staking(&users.alice, MAX_DURATION_SEC)
+        .assert_success();
+
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, 0);
+
+    let account = e.get_account(&users.alice);
+    let booster_staking = account.booster_staking.unwrap();
+    assert_eq!(booster_staking.staked_booster_amount, amount);
+    assert_eq!(booster_staking.x_booster_amount, amount * 4);
+    assert_eq!(
+        booster_staking.unlock_timestamp,
+        GENESIS_TIMESTAMP + sec_to_nano(MIN_DURATION_SEC / 2 + MAX_DURATION_SEC)
+    );
+    // The farms are updated with the new xBooster amount.
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, amount * 4);
+
+    // The staking can't be shortened.
+    assert!(!e
+        .account_extend_booster_staking(&users.alice, MIN_DURATION_SEC)
+        .is_ok());
+}
+
+#[test]
+fn test_booster_partial_unstake() {
+    let (e, _tokens, users) = basic_setup();
+
+    let amount = d(100, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, amount, "")
+        .assert_success();
+
+    e.account_stake_booster(&users.alice, amount, MAX_DURATION_SEC)
+        .assert_success();
+
+    assert!(!e
+        .account_unstake_booster_amount(&users.alice, Some(amount / 4))
+        .is_ok());
+
+    e.skip_time(MAX_DURATION_SEC);
+
+    assert!(!e
+        .account_unstake_booster_amount(&users.alice, Some(amount * 2))
+        .is_ok());
+    assert!(!e
+        .account_unstake_booster_amount(&users.alice, Some(0))
+        .is_ok());
+
+    e.account_unstake_booster_amount(&users.alice, Some(amount / 4))
+        .assert_success();
+
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, amount / 4);
+
+    let account = e.get_account(&users.alice);
+    let booster_staking = account.booster_staking.unwrap();
+    assert_eq!(booster_staking.staked_booster_amount, amount * 3 / 4);
+    assert_eq!(booster_staking.x_booster_amount, amount * 3);
+
+    e.account_unstake_booster(&users.alice).assert_success();
+
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, amount);
+
+    let account = e.get_account(&users.alice);
+    assert!(account.booster_staking.is_none());
+}
+
+#[test]
+fn test_booster_decay() {
+    let (e, tokens, users) = basic_setup();
+
+    let mut config = e.get_config();
+    config.booster_decay_enabled = true;
+    e.update_config(config).assert_success();
+
+    e.add_farm(
+        FarmId::BoosterStaking,
+        &tokens.nusdc,
+        d(100, 18),
+        0,
+        d(3000, 18),
+    );
+
+    let amount = d(100, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, amount, "")
+        .assert_success();
+
+    e.account_stake_booster(&users.alice, amount, MAX_DURATION_SEC)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.booster_staking.unwrap().x_booster_amount,
+        amount * 4
+    );
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, amount * 4);
+
+    // Half way between the maximum and the minimum remaining duration.
+    e.skip_time((MAX_DURATION_SEC - MIN_DURATION_SEC) / 2);
+
+    // The boosted shares are stale until the account is poked.
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.booster_staking.unwrap().x_booster_amount,
+        amount * 4
+    );
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, amount * 4);
+
+    e.account_farm_poke(&users.bob, &users.alice)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, amount * 5 / 2);
+
+    // Below the minimum remaining duration the xBooster fully decays to the staked amount.
+    e.skip_time(MAX_DURATION_SEC / 2);
+
+    e.account_farm_poke(&users.bob, &users.alice)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, amount);
+}
+
+#[test]
+fn test_booster_early_unstake() {
+    let (e, _tokens, users) = basic_setup();
+
+    let amount = d(100, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, amount, "")
+        .assert_success();
+
+    e.account_stake_booster(&users.alice, amount, MAX_DURATION_SEC)
+        .assert_success();
+
+    let mut config = e.get_config();
+    config.booster_early_unstake_penalty = None;
+    e.update_config(config).assert_success();
+
+    // The early unstaking is disabled.
+    assert!(!e
+        .account_early_unstake_booster(&users.alice, Some(amount / 2))
+        .is_ok());
+
+    let mut config = e.get_config();
+    config.booster_early_unstake_penalty = Some(5000);
+    e.update_config(config).assert_success();
+
+    let reserved = e.get_asset(&e.booster_token).reserved;
+
+    // With the maximum staking duration remaining, the penalty is 50%.
+    e.account_early_unstake_booster(&users.alice, Some(amount / 2))
+        .assert_success();
+
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, amount / 4);
+    assert_eq!(asset.reserved, reserved + amount / 4);
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.supplied[0].balance, amount / 4);
+    let booster_staking = account.booster_staking.unwrap();
+    assert_eq!(booster_staking.staked_booster_amount, amount / 2);
+    assert_eq!(booster_staking.x_booster_amount, amount * 2);
+
+    e.skip_time(MAX_DURATION_SEC / 2);
+
+    // With half of the maximum staking duration remaining, the penalty is 25%.
+    e.account_early_unstake_booster(&users.alice, None)
+        .assert_success();
+
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, amount * 5 / 8);
+    assert_eq!(asset.reserved, reserved + amount * 3 / 8);
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.supplied[0].balance, amount * 5 / 8);
+    assert!(account.booster_staking.is_none());
+}
@@ -0,0 +1,112 @@
+mod setup;
+
+use crate::setup::*;
+
+fn borrow_setup() -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    // 100 NEAR * $10 * 60% = $600 of borrowing power. Borrowing $500 of DAI.
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(500, 18),
+    )
+    .assert_success();
+
+    (e, tokens, users)
+}
+
+#[test]
+fn test_deleverage_with_reserves() {
+    let (e, tokens, users) = borrow_setup();
+
+    let wnear_reserved = e.get_asset(&tokens.wnear).reserved;
+    let ndai_reserved = e.get_asset(&tokens.ndai).reserved;
+
+    // 10 NEAR = $100. With 1% fee it repays 99 DAI.
+    e.deleverage(
+        &users.alice,
+        asset_amount(&tokens.wnear, d(10, 24)),
+        &tokens.ndai,
+        None,
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(90, 24))],
+    );
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(401, 18),
+        18,
+    );
+
+    assert_eq!(
+        e.get_asset(&tokens.wnear).reserved,
+        wnear_reserved + d(10, 24)
+    );
+    almost_eq(
+        e.get_asset(&tokens.ndai).reserved,
+        ndai_reserved - d(99, 18),
+        18,
+    );
+}
+
+#[test]
+fn test_deleverage_with_dex() {
+    let (e, tokens, users) = borrow_setup();
+
+    // 1 NEAR = 10 DAI
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+
+    e.deleverage(
+        &users.alice,
+        asset_amount(&tokens.wnear, d(10, 24)),
+        &tokens.ndai,
+        Some(d(99, 18)),
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(90, 24))],
+    );
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(400, 18),
+        18,
+    );
+}
+
+#[test]
+fn test_deleverage_at_risk() {
+    let (e, tokens, users) = borrow_setup();
+
+    // At $8 per NEAR, 100 NEAR * $8 * 60% = $480 of borrowing power for $500 of DAI.
+    // 10 NEAR = $80. With 1% fee it repays 79.2 DAI and the health factor increases.
+    e.deleverage(
+        &users.alice,
+        asset_amount(&tokens.wnear, d(10, 24)),
+        &tokens.ndai,
+        None,
+        price_data(&tokens, Some(80000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(4208, 17),
+        18,
+    );
+}
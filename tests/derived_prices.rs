@@ -0,0 +1,146 @@
+mod setup;
+
+use crate::setup::*;
+use common::{AssetOptionalPrice, Price, PriceData};
+use contract::{DerivedPrice, ExchangeRateSource};
+
+const RATE_ASSET_ID: &str = "rate.neth.near";
+
+/// nETH is priced as 2 NEAR, so 1 nETH is $20 at the NEAR price of $10.
+const NETH_RATE: u128 = 2 * 10u128.pow(24 - 18);
+
+fn derived_price_setup(exchange_rate: ExchangeRateSource) -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    let mut asset_config = e.get_asset(&tokens.neth).config;
+    asset_config.derived_price = Some(DerivedPrice {
+        underlying_token_id: tokens.wnear.account_id(),
+        exchange_rate,
+    });
+    e.update_asset(&tokens.neth, asset_config).assert_success();
+
+    e.supply_to_collateral(&users.alice, &tokens.neth, d(100, 18))
+        .assert_success();
+
+    (e, tokens, users)
+}
+
+fn with_rate(mut price_data: PriceData, rate: u128) -> PriceData {
+    price_data.prices.push(AssetOptionalPrice {
+        asset_id: RATE_ASSET_ID.to_string(),
+        price: Some(Price {
+            multiplier: rate,
+            decimals: 0,
+        }),
+    });
+    price_data
+}
+
+#[test]
+fn test_derived_price_from_oracle_rate() {
+    let (e, tokens, users) = derived_price_setup(ExchangeRateSource::Oracle {
+        rate_asset_id: a(RATE_ASSET_ID),
+    });
+
+    // Without the exchange rate the nETH price is missing.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(100000), None),
+            d(1000, 18),
+        )
+        .is_ok());
+
+    // 100 nETH * $20 * 60% = $1200 of borrowing power.
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        with_rate(price_data(&tokens, Some(100000), None), NETH_RATE),
+        d(1000, 18),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.ndai.account_id(), d(1000, 18))],
+    );
+}
+
+#[test]
+fn test_derived_price_ignores_oracle_price() {
+    let (e, tokens, users) = derived_price_setup(ExchangeRateSource::Oracle {
+        rate_asset_id: a(RATE_ASSET_ID),
+    });
+
+    // The oracle price of $1000 per nETH is ignored, so the borrowing power is still $1200.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            with_rate(price_data(&tokens, Some(100000), Some(10000000)), NETH_RATE),
+            d(1200, 18),
+        )
+        .is_ok());
+
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+}
+
+#[test]
+fn test_derived_price_from_contract_rate() {
+    let (e, tokens, users) = derived_price_setup(ExchangeRateSource::Contract {
+        contract_id: a(PRICE_FEED_ID),
+        method_name: "get_exchange_rate".to_string(),
+        decimals: 6,
+    });
+
+    e.set_exchange_rate(NETH_RATE * 10u128.pow(6))
+        .assert_success();
+
+    // The exchange rate is not cached yet.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(100000), None),
+            d(1000, 18),
+        )
+        .is_ok());
+
+    e.update_exchange_rate(&tokens.neth).assert_success();
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(1000, 18),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.ndai.account_id(), d(1000, 18))],
+    );
+
+    // The cached exchange rate is older than the maximum staleness, so the nETH price is stale.
+    e.skip_time(60);
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(100000), None),
+            d(100, 18),
+        )
+        .is_ok());
+
+    e.update_exchange_rate(&tokens.neth).assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(100, 18),
+    )
+    .assert_success();
+}
@@ -1,6 +1,7 @@
 mod setup;
 
 use crate::setup::*;
+use contract::FarmId;
 use near_sdk::serde_json;
 
 #[test]
@@ -32,3 +33,52 @@ fn test_deposit_event() {
         tokens.wnear.account_id().as_str()
     );
 }
+
+#[test]
+fn test_farm_reward_claimed_event() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        d(3000, 18),
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    e.skip_time(ONE_DAY_SEC * 3);
+
+    e.account_farm_claim_all(&users.alice).assert_success();
+    let logs = get_logs(&e.near.borrow_runtime());
+    let event = &logs[0];
+    assert!(event.starts_with(EVENT_JSON));
+
+    let value: serde_json::Value =
+        serde_json::from_str(&event[EVENT_JSON.len()..]).expect("Failed to parse the event");
+    assert_eq!(value["event"].as_str().unwrap(), "farm_reward_claimed");
+    assert_eq!(
+        value["data"][0]["account_id"].as_str().unwrap(),
+        users.alice.account_id().as_str()
+    );
+    assert_eq!(
+        value["data"][0]["farm_id"],
+        serde_json::to_value(&farm_id).unwrap()
+    );
+    assert_eq!(
+        value["data"][0]["token_id"].as_str().unwrap(),
+        e.booster_token.account_id().as_str()
+    );
+    assert_eq!(
+        value["data"][0]["amount"].as_str().unwrap(),
+        (reward_per_day * 3).to_string()
+    );
+    assert_eq!(
+        value["data"][0]["total_claimed_amount"].as_str().unwrap(),
+        (reward_per_day * 3).to_string()
+    );
+}
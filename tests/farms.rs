@@ -2,7 +2,7 @@ mod setup;
 
 use crate::setup::*;
 use common::ONE_YOCTO;
-use contract::FarmId;
+use contract::{BoostCurve, FarmId, FarmRewardSchedule, PriceMode};
 use near_sdk::json_types::U128;
 
 #[test]
@@ -1198,6 +1198,10 @@ fn test_farm_net_tvl_multipliers() {
                     can_use_as_collateral: true,
                     can_borrow: true,
                     net_tvl_multiplier: 8000,
+                    collateral_price_mode: PriceMode::Spot,
+                    debt_price_mode: PriceMode::Spot,
+                    derived_price: None,
+                    max_price_staleness_sec: None,
                 },
             ),
             DEFAULT_GAS.0,
@@ -1231,3 +1235,668 @@ fn test_farm_net_tvl_multipliers() {
     // 100 - 4 * 10 * 0.8 + 10 * 10 * 0.8
     assert_eq!(account.farms[0].rewards[0].boosted_shares, d(148, 18));
 }
+
+#[test]
+fn test_farm_reward_schedule() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(1000, 18);
+    let initial_reserved = e.get_asset(&e.booster_token).reserved;
+
+    let block_timestamp = e.near.borrow_runtime().cur_block.block_timestamp;
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm_with_schedule(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+        Some(FarmRewardSchedule {
+            start_timestamp: Some(block_timestamp + to_nano(ONE_DAY_SEC)),
+            end_timestamp: Some(block_timestamp + to_nano(ONE_DAY_SEC * 3)),
+        }),
+    );
+    assert_eq!(
+        e.get_asset(&e.booster_token).reserved,
+        initial_reserved - total_reward
+    );
+
+    // Updating the reward without a schedule keeps the existing schedule.
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        0,
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    // The distribution hasn't started yet.
+    e.skip_time(ONE_DAY_SEC);
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].unclaimed_amount, 0);
+
+    e.skip_time(ONE_DAY_SEC);
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].unclaimed_amount, reward_per_day);
+
+    // The distribution ends after 2 days.
+    e.skip_time(ONE_DAY_SEC * 5);
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.farms[0].rewards[0].unclaimed_amount,
+        reward_per_day * 2
+    );
+
+    e.account_farm_claim_all(&users.alice).assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.supplied,
+        &[
+            av(tokens.ndai.account_id(), d(100, 18)),
+            av(e.booster_token.account_id(), reward_per_day * 2),
+        ],
+    );
+
+    // The undistributed reward is returned to the reserve.
+    let asset = e.get_asset(&tokens.ndai);
+    assert!(asset.farms[0].rewards.is_empty());
+    assert_eq!(
+        e.get_asset(&e.booster_token).reserved,
+        initial_reserved - reward_per_day * 2
+    );
+}
+
+#[test]
+fn test_farm_reward_schedule_in_the_past() {
+    let (e, tokens, _users) = basic_setup();
+
+    let block_timestamp = e.near.borrow_runtime().cur_block.block_timestamp;
+    let result = e.owner.function_call(
+        e.contract.contract.add_asset_farm_reward(
+            FarmId::Supplied(tokens.ndai.account_id()),
+            e.booster_token.account_id(),
+            U128(d(100, 18)),
+            U128(d(100, 18)),
+            U128(d(1000, 18)),
+            Some(FarmRewardSchedule {
+                start_timestamp: None,
+                end_timestamp: Some(block_timestamp - 1),
+            }),
+            None,
+        ),
+        DEFAULT_GAS.0,
+        ONE_YOCTO,
+    );
+    assert!(!result.is_ok());
+}
+
+#[test]
+fn test_farm_reward_top_up_after_schedule_end() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(1000, 18);
+
+    let block_timestamp = e.near.borrow_runtime().cur_block.block_timestamp;
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm_with_schedule(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+        Some(FarmRewardSchedule {
+            start_timestamp: None,
+            end_timestamp: Some(block_timestamp + to_nano(ONE_DAY_SEC)),
+        }),
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    // The distribution ends after 1 day.
+    e.skip_time(ONE_DAY_SEC * 2);
+    e.account_farm_claim_all(&users.alice).assert_success();
+    let asset = e.get_asset(&tokens.ndai);
+    assert!(asset.farms[0].rewards.is_empty());
+
+    // Topping up the ended reward without a schedule distributes it again.
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+    );
+
+    e.skip_time(ONE_DAY_SEC);
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.farms[0].rewards.len(), 1);
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].unclaimed_amount, reward_per_day);
+}
+
+#[test]
+fn test_cancel_farm_reward() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(1000, 18);
+    let initial_reserved = e.get_asset(&e.booster_token).reserved;
+
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    e.skip_time(ONE_DAY_SEC * 2);
+
+    // Only the owner can cancel the reward.
+    assert!(!users
+        .alice
+        .function_call(
+            e.contract.contract.cancel_asset_farm_reward(
+                farm_id.clone(),
+                e.booster_token.account_id(),
+                None,
+            ),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+        .is_ok());
+    e.cancel_farm_reward(farm_id.clone(), &e.booster_token, Some(d(300, 18)))
+        .assert_success();
+
+    let asset = e.get_asset(&tokens.ndai);
+    let booster_reward = asset.farms[0]
+        .rewards
+        .get(&e.booster_token.account_id())
+        .cloned()
+        .unwrap();
+    assert_eq!(
+        booster_reward.remaining_rewards,
+        total_reward - reward_per_day * 2 - d(300, 18)
+    );
+    assert_eq!(
+        e.get_asset(&e.booster_token).reserved,
+        initial_reserved - total_reward + d(300, 18)
+    );
+
+    e.skip_time(ONE_DAY_SEC);
+    e.cancel_farm_reward(farm_id.clone(), &e.booster_token, None)
+        .assert_success();
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert!(asset.farms[0].rewards.is_empty());
+    assert_eq!(
+        e.get_asset(&e.booster_token).reserved,
+        initial_reserved - reward_per_day * 3
+    );
+
+    // The rewards distributed before the cancellation can still be claimed.
+    e.skip_time(ONE_DAY_SEC * 2);
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.farms[0].rewards[0].unclaimed_amount,
+        reward_per_day * 3
+    );
+
+    e.account_farm_claim_all(&users.alice).assert_success();
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.supplied,
+        &[
+            av(tokens.ndai.account_id(), d(100, 18)),
+            av(e.booster_token.account_id(), reward_per_day * 3),
+        ],
+    );
+
+    assert!(!e
+        .cancel_farm_reward(farm_id, &e.booster_token, None)
+        .is_ok());
+}
+
+#[test]
+fn test_farm_claim_to_wallet() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(3000, 18);
+
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    e.skip_time(ONE_DAY_SEC * 3);
+
+    // Only the account owner can claim the rewards to the wallet.
+    assert!(!users
+        .bob
+        .function_call(
+            e.contract
+                .contract
+                .account_farm_claim_all(Some(users.alice.account_id()), Some(true)),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+        .is_ok());
+
+    // Claiming to the wallet requires one yoctoNEAR.
+    assert!(!users
+        .alice
+        .function_call(
+            e.contract.contract.account_farm_claim_all(None, Some(true)),
+            MAX_GAS.0,
+            0,
+        )
+        .is_ok());
+
+    let wallet_balance = e.ft_balance_of(&e.booster_token, &users.alice);
+    e.account_farm_claim_all_to_wallet(&users.alice)
+        .assert_success();
+
+    let farmed_amount = reward_per_day * 3;
+    assert_eq!(
+        e.ft_balance_of(&e.booster_token, &users.alice),
+        wallet_balance + farmed_amount
+    );
+
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, 0);
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.supplied,
+        &[av(tokens.ndai.account_id(), d(100, 18))],
+    );
+    assert_eq!(account.farms.len(), 1);
+    assert_eq!(account.farms[0].rewards[0].unclaimed_amount, 0);
+}
+
+#[test]
+fn test_farm_claim_to_wallet_failed_transfer() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(3000, 18);
+
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+    );
+
+    // Eve is not registered with the booster token, so the reward transfer fails.
+    ft_storage_deposit(
+        &users.eve,
+        &tokens.ndai.account_id(),
+        &users.eve.account_id(),
+    );
+    e.mint_ft(&tokens.ndai, &users.eve, d(100, 18));
+    storage_deposit(
+        &users.eve,
+        &e.contract.account_id(),
+        &users.eve.account_id(),
+        d(1, 23),
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.eve, d(100, 18), "")
+        .assert_success();
+
+    e.skip_time(ONE_DAY_SEC * 3);
+
+    e.account_farm_claim_all_to_wallet(&users.eve)
+        .assert_success();
+
+    // The reward is deposited to the supplied balance instead.
+    let farmed_amount = reward_per_day * 3;
+    let asset = e.get_asset(&e.booster_token);
+    assert_eq!(asset.supplied.balance, farmed_amount);
+
+    let account = e.get_account(&users.eve);
+    assert_balances(
+        &account.supplied,
+        &[
+            av(tokens.ndai.account_id(), d(100, 18)),
+            av(e.booster_token.account_id(), farmed_amount),
+        ],
+    );
+}
+
+#[test]
+fn test_farm_booster_staking() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(3000, 18);
+
+    let farm_id = FarmId::BoosterStaking;
+    e.add_farm(
+        farm_id.clone(),
+        &tokens.nusdc,
+        reward_per_day,
+        0,
+        total_reward,
+    );
+
+    let booster_amount = d(5, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, booster_amount, "")
+        .assert_success();
+
+    // Supplied booster tokens are not farmed by the booster staking farm.
+    let account = e.get_account(&users.alice);
+    assert!(account.farms.is_empty());
+    assert!(!account.has_non_farmed_assets);
+
+    e.account_stake_booster(&users.alice, booster_amount, MAX_DURATION_SEC)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    let x_booster_amount = account.booster_staking.unwrap().x_booster_amount;
+    assert_eq!(x_booster_amount, booster_amount * 4);
+    assert_eq!(account.farms.len(), 1);
+    assert_eq!(account.farms[0].farm_id, farm_id);
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, x_booster_amount);
+
+    let asset_farm = e.get_asset_farm(farm_id.clone());
+    let reward = asset_farm
+        .rewards
+        .get(&tokens.nusdc.account_id())
+        .cloned()
+        .unwrap();
+    assert_eq!(reward.boosted_shares, x_booster_amount);
+
+    e.skip_time(ONE_DAY_SEC * 3);
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.farms[0].rewards[0].unclaimed_amount,
+        reward_per_day * 3
+    );
+
+    e.skip_time(MAX_DURATION_SEC);
+    e.account_unstake_booster(&users.alice).assert_success();
+
+    // The farm is removed once the booster token is unstaked.
+    let account = e.get_account(&users.alice);
+    assert!(account.farms.is_empty());
+    assert_eq!(
+        find_asset(&account.supplied, &e.booster_token.account_id()).balance,
+        booster_amount
+    );
+    almost_eq(
+        find_asset(&account.supplied, &tokens.nusdc.account_id()).balance,
+        total_reward,
+        18,
+    );
+
+    let asset_farm = e.get_asset_farm(farm_id);
+    assert!(asset_farm.rewards.is_empty());
+}
+
+#[test]
+fn test_farm_collateral() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(3000, 18);
+
+    let farm_id = FarmId::Collateral(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        total_reward,
+    );
+
+    let amount = d(100, 18);
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, amount, "")
+        .assert_success();
+    e.supply_to_collateral(&users.bob, &tokens.ndai, amount)
+        .assert_success();
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.farms.len(), 1);
+    assert_eq!(asset.farms[0].farm_id, farm_id);
+
+    // Only the collateral shares are farmed.
+    let account = e.get_account(&users.alice);
+    assert!(account.farms.is_empty());
+
+    let account = e.get_account(&users.bob);
+    assert_eq!(account.farms.len(), 1);
+    assert_eq!(account.farms[0].farm_id, farm_id);
+    assert_eq!(
+        account.farms[0].rewards[0].boosted_shares,
+        find_asset(&account.collateral, &tokens.ndai.account_id())
+            .shares
+            .0,
+    );
+
+    e.skip_time(ONE_DAY_SEC * 2);
+
+    // Alice moves all of her supplied balance together with the new deposit to the collateral.
+    e.supply_to_collateral(&users.alice, &tokens.ndai, amount)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms.len(), 1);
+    assert_eq!(account.farms[0].rewards[0].unclaimed_amount, 0);
+
+    e.skip_time(ONE_DAY_SEC * 3);
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        account.farms[0].rewards[0].unclaimed_amount,
+        reward_per_day * 2,
+        18,
+    );
+
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        account.farms[0].rewards[0].unclaimed_amount,
+        reward_per_day * 3,
+        18,
+    );
+}
+
+#[test]
+fn test_farm_claimed_rewards_history() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &e.booster_token,
+        reward_per_day,
+        d(100, 18),
+        d(3000, 18),
+    );
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    e.skip_time(ONE_DAY_SEC * 3);
+
+    // Withdrawing everything claims the rewards and stops farming.
+    e.execute(
+        &users.alice,
+        vec![Action::Withdraw(AssetAmount {
+            token_id: tokens.ndai.account_id(),
+            amount: None,
+            max_amount: None,
+        })],
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.farms.is_empty());
+    assert_eq!(account.claimed_farm_rewards.len(), 1);
+    let claimed_farm_reward = &account.claimed_farm_rewards[0];
+    assert_eq!(claimed_farm_reward.farm_id, farm_id);
+    assert_eq!(
+        claimed_farm_reward.reward_token_id,
+        e.booster_token.account_id()
+    );
+    assert_eq!(claimed_farm_reward.claimed_amount, reward_per_day * 3);
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    e.skip_time(ONE_DAY_SEC * 2);
+
+    e.account_farm_claim_all(&users.alice).assert_success();
+
+    // The lifetime total includes the rewards claimed before.
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.claimed_farm_rewards.len(), 1);
+    assert_eq!(
+        account.claimed_farm_rewards[0].claimed_amount,
+        reward_per_day * 5
+    );
+}
+
+#[test]
+fn test_farm_capped_linear_boost_curve() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(3000, 18);
+
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm(
+        farm_id.clone(),
+        &tokens.nusdc,
+        reward_per_day,
+        d(20, 18),
+        total_reward,
+    );
+    e.update_farm_boost_curve(
+        farm_id.clone(),
+        &tokens.nusdc,
+        BoostCurve::CappedLinear {
+            max_multiplier: 25000,
+            full_boost_x_booster_amount: d(40, 18),
+        },
+    )
+    .assert_success();
+
+    // The boost curve can only be updated for an active reward.
+    assert!(!e
+        .update_farm_boost_curve(farm_id.clone(), &e.booster_token, BoostCurve::None)
+        .is_ok());
+
+    let booster_amount = d(5, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, booster_amount, "")
+        .assert_success();
+    e.account_stake_booster(&users.alice, booster_amount, MAX_DURATION_SEC)
+        .assert_success();
+
+    let amount = d(100, 18);
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, amount, "")
+        .assert_success();
+
+    // 20 xBooster is half of the full boost amount, so the multiplier is 1.75.
+    let account = e.get_account(&users.alice);
+    let shares = find_asset(&account.supplied, &tokens.ndai.account_id())
+        .shares
+        .0;
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, shares * 7 / 4);
+    assert_eq!(
+        account.farms[0].rewards[0].boost_multiplier.to_string(),
+        "1.75"
+    );
+
+    e.update_farm_boost_curve(farm_id.clone(), &tokens.nusdc, BoostCurve::None)
+        .assert_success();
+
+    // The expected multiplier changes immediately, but the boosted shares are updated on claim.
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, shares * 7 / 4);
+    assert_eq!(
+        account.farms[0].rewards[0].boost_multiplier.to_string(),
+        "1.0"
+    );
+
+    e.account_farm_claim_all(&users.alice).assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.farms[0].rewards[0].boosted_shares, shares);
+}
+
+#[test]
+fn test_farm_add_reward_with_boost_curve() {
+    let (e, tokens, users) = basic_setup();
+
+    let reward_per_day = d(100, 18);
+    let total_reward = d(3000, 18);
+
+    let farm_id = FarmId::Supplied(tokens.ndai.account_id());
+    e.add_farm_with_boost_curve(
+        farm_id.clone(),
+        &tokens.nusdc,
+        reward_per_day,
+        d(20, 18),
+        total_reward,
+        BoostCurve::CappedLinear {
+            max_multiplier: 25000,
+            full_boost_x_booster_amount: d(40, 18),
+        },
+    );
+
+    let booster_amount = d(5, 18);
+    e.contract_ft_transfer_call(&e.booster_token, &users.alice, booster_amount, "")
+        .assert_success();
+    e.account_stake_booster(&users.alice, booster_amount, MAX_DURATION_SEC)
+        .assert_success();
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.alice, d(100, 18), "")
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.farms[0].rewards[0].boost_multiplier.to_string(),
+        "1.75"
+    );
+
+    // Topping up the reward without a boost curve keeps the existing curve.
+    e.add_farm(
+        farm_id.clone(),
+        &tokens.nusdc,
+        reward_per_day,
+        d(20, 18),
+        total_reward,
+    );
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.farms[0].rewards[0].boost_multiplier.to_string(),
+        "1.75"
+    );
+}
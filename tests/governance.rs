@@ -0,0 +1,178 @@
+mod setup;
+
+use crate::setup::*;
+use contract::{GovernanceConfig, ProposalAction, Role};
+
+const VOTING_DURATION_SEC: u32 = 24 * 60 * 60;
+const TIMELOCK_DURATION_SEC: u32 = 2 * 24 * 60 * 60;
+
+fn governance_setup() -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    let mut config = e.get_config();
+    config.governance = Some(GovernanceConfig {
+        min_proposal_x_booster_amount: d(100, 18),
+        voting_duration_sec: VOTING_DURATION_SEC,
+        timelock_duration_sec: TIMELOCK_DURATION_SEC,
+        quorum_x_booster_amount: d(500, 18),
+    });
+    e.update_config(config).assert_success();
+
+    // Both Alice and Bob get 400 xBooster.
+    for user in [&users.alice, &users.bob] {
+        e.contract_ft_transfer_call(&e.booster_token, user, d(100, 18), "")
+            .assert_success();
+        e.account_stake_booster(user, d(100, 18), MAX_DURATION_SEC)
+            .assert_success();
+    }
+
+    (e, tokens, users)
+}
+
+#[test]
+fn test_governance_update_asset() {
+    let (e, tokens, users) = governance_setup();
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    assert!(asset_config.can_borrow);
+    asset_config.can_borrow = false;
+    let action = ProposalAction::UpdateAsset {
+        token_id: tokens.ndai.account_id(),
+        asset_config,
+    };
+
+    // Charlie doesn't have xBooster.
+    assert!(!e.create_proposal(&users.charlie, action.clone()).is_ok());
+
+    let proposal_id: u64 = e.create_proposal(&users.alice, action).unwrap_json();
+    assert_eq!(proposal_id, 0);
+
+    assert!(!e.vote_proposal(&users.charlie, proposal_id, true).is_ok());
+    e.vote_proposal(&users.alice, proposal_id, true)
+        .assert_success();
+    assert!(!e.vote_proposal(&users.alice, proposal_id, true).is_ok());
+    e.vote_proposal(&users.bob, proposal_id, true)
+        .assert_success();
+
+    let proposal = e.get_proposal(proposal_id).proposal;
+    assert_eq!(proposal.votes_for, d(800, 18));
+    assert_eq!(proposal.votes_against, 0);
+    assert!(!proposal.executed);
+
+    e.skip_time(VOTING_DURATION_SEC);
+
+    // The timelock has not passed yet.
+    assert!(!e.execute_proposal(&users.charlie, proposal_id).is_ok());
+
+    e.skip_time(TIMELOCK_DURATION_SEC);
+
+    e.execute_proposal(&users.charlie, proposal_id)
+        .assert_success();
+    assert!(!e.get_asset(&tokens.ndai).config.can_borrow);
+    assert!(e.get_proposal(proposal_id).proposal.executed);
+
+    assert!(!e.execute_proposal(&users.charlie, proposal_id).is_ok());
+}
+
+#[test]
+fn test_governance_rejected_proposal() {
+    let (e, tokens, users) = governance_setup();
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow = false;
+    let proposal_id: u64 = e
+        .create_proposal(
+            &users.alice,
+            ProposalAction::UpdateAsset {
+                token_id: tokens.ndai.account_id(),
+                asset_config,
+            },
+        )
+        .unwrap_json();
+
+    // The quorum is not reached.
+    e.vote_proposal(&users.alice, proposal_id, true)
+        .assert_success();
+
+    e.skip_time(VOTING_DURATION_SEC);
+
+    // The voting has ended.
+    assert!(!e.vote_proposal(&users.bob, proposal_id, true).is_ok());
+
+    e.skip_time(TIMELOCK_DURATION_SEC);
+
+    assert!(!e.execute_proposal(&users.alice, proposal_id).is_ok());
+    assert!(e.get_asset(&tokens.ndai).config.can_borrow);
+}
+
+#[test]
+fn test_governance_vote_locks_booster_staking() {
+    let (e, tokens, users) = governance_setup();
+
+    let mut config = e.get_config();
+    config.booster_early_unstake_penalty = Some(5000);
+    e.update_config(config).assert_success();
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow = false;
+    let proposal_id: u64 = e
+        .create_proposal(
+            &users.alice,
+            ProposalAction::UpdateAsset {
+                token_id: tokens.ndai.account_id(),
+                asset_config,
+            },
+        )
+        .unwrap_json();
+    e.vote_proposal(&users.alice, proposal_id, true)
+        .assert_success();
+
+    // Alice can't unstake to vote again with the same stake from another account.
+    assert!(!e.account_early_unstake_booster(&users.alice, None).is_ok());
+
+    // Bob didn't vote, so he can still unstake.
+    e.account_early_unstake_booster(&users.bob, None)
+        .assert_success();
+
+    e.skip_time(VOTING_DURATION_SEC);
+
+    e.account_early_unstake_booster(&users.alice, None)
+        .assert_success();
+    assert!(e.get_account(&users.alice).booster_staking.is_none());
+}
+
+#[test]
+fn test_guardian_cancels_proposal() {
+    let (e, tokens, users) = governance_setup();
+
+    e.grant_role(&e.owner, &users.charlie, Role::Guardian)
+        .assert_success();
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow = false;
+    let proposal_id: u64 = e
+        .create_proposal(
+            &users.alice,
+            ProposalAction::UpdateAsset {
+                token_id: tokens.ndai.account_id(),
+                asset_config,
+            },
+        )
+        .unwrap_json();
+    for user in [&users.alice, &users.bob] {
+        e.vote_proposal(user, proposal_id, true).assert_success();
+    }
+
+    e.skip_time(VOTING_DURATION_SEC);
+
+    // Only a guardian can cancel the proposal.
+    assert!(!e.cancel_proposal(&users.bob, proposal_id).is_ok());
+    e.cancel_proposal(&users.charlie, proposal_id)
+        .assert_success();
+    assert!(e.get_proposal(proposal_id).proposal.cancelled);
+
+    e.skip_time(TIMELOCK_DURATION_SEC);
+
+    assert!(!e.execute_proposal(&users.alice, proposal_id).is_ok());
+    assert!(e.get_asset(&tokens.ndai).config.can_borrow);
+}
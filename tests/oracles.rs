@@ -0,0 +1,306 @@
+mod setup;
+
+use crate::setup::*;
+use contract::{PriceMode, PriceReceiverMsg};
+use near_sdk::AccountId;
+use near_sdk_sim::ContractAccount;
+use test_oracle::ContractContract as OracleContract;
+
+const ORACLE2_ID: &str = "oracle2.near";
+const ORACLE3_ID: &str = "oracle3.near";
+
+fn multi_oracle_setup() -> (
+    Env,
+    Tokens,
+    Users,
+    ContractAccount<OracleContract>,
+    ContractAccount<OracleContract>,
+) {
+    let (e, tokens, users) = basic_setup();
+
+    let oracle2 = e.deploy_oracle(ORACLE2_ID);
+    let oracle3 = e.deploy_oracle(ORACLE3_ID);
+    let mut config = e.get_config();
+    config.secondary_oracle_account_ids = vec![
+        AccountId::new_unchecked(ORACLE2_ID.to_string()),
+        AccountId::new_unchecked(ORACLE3_ID.to_string()),
+    ];
+    e.update_config(config).assert_success();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    (e, tokens, users, oracle2, oracle3)
+}
+
+#[test]
+fn test_multi_oracle_prices_agree() {
+    let (e, tokens, users, oracle2, oracle3) = multi_oracle_setup();
+
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(102000), None))
+        .assert_success();
+    e.oracle_commit(&oracle3, price_data(&tokens, Some(99000), None))
+        .assert_success();
+
+    // The median price is $10, so 100 NEAR * $10 * 60% = $600 of borrowing power.
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(500, 18),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.borrowed.len(), 1);
+}
+
+#[test]
+fn test_multi_oracle_median_ignores_outlier() {
+    let (e, tokens, users, oracle2, oracle3) = multi_oracle_setup();
+
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(100000), None))
+        .assert_success();
+    e.oracle_commit(&oracle3, price_data(&tokens, Some(101000), None))
+        .assert_success();
+
+    // The primary oracle price of $20 deviates by more than 5% from the median of $10.1, so the
+    // NEAR price is rejected.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(200000), None),
+            d(1000, 18),
+        )
+        .is_ok());
+
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+}
+
+#[test]
+fn test_multi_oracle_min_sources() {
+    let (e, tokens, users, oracle2, _oracle3) = multi_oracle_setup();
+
+    let mut config = e.get_config();
+    config.min_oracle_sources = 2;
+    e.update_config(config).assert_success();
+
+    // The other oracles haven't committed any prices, so the NEAR price is rejected.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(100000), None),
+            d(500, 18),
+        )
+        .is_ok());
+
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(100000), None))
+        .assert_success();
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(500, 18),
+    )
+    .assert_success();
+
+    // The price committed by the second oracle becomes stale.
+    e.skip_time(61);
+
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(100000), None),
+            d(10, 18),
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_multi_oracle_price_timestamp_is_oldest_source() {
+    let (e, tokens, users, oracle2, _oracle3) = multi_oracle_setup();
+
+    let mut asset_config = e.get_asset(&tokens.wnear).config;
+    asset_config.max_price_staleness_sec = Some(5);
+    e.update_asset(&tokens.wnear, asset_config).assert_success();
+
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(100000), None))
+        .assert_success();
+    e.skip_time(10);
+
+    // The committed NEAR price is in the median, but it's older than 5 seconds.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(100000), None),
+            d(500, 18),
+        )
+        .is_ok());
+
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(100000), None))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(500, 18),
+    )
+    .assert_success();
+}
+
+#[test]
+fn test_unknown_oracle() {
+    let (e, tokens, _users, _oracle2, _oracle3) = multi_oracle_setup();
+
+    let unknown_oracle = e.deploy_oracle("oracle4.near");
+    assert!(!e
+        .oracle_commit(&unknown_oracle, price_data(&tokens, Some(100000), None))
+        .is_ok());
+}
+
+#[test]
+fn test_price_change_circuit_breaker() {
+    let (e, tokens, users) = basic_setup();
+
+    let mut config = e.get_config();
+    config.price_change_window_sec = 60;
+    e.update_config(config).assert_success();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(100, 18),
+    )
+    .assert_success();
+
+    // The NEAR price jumps by 30% within the window, so the collateral price is rejected.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(130000), None),
+            d(100, 18),
+        )
+        .is_ok());
+
+    // An empty batch with the jumped price doesn't make it the new reference price.
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(130000), None),
+        PriceReceiverMsg::Execute { actions: vec![] },
+    )
+    .assert_success();
+
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(130000), None),
+            d(100, 18),
+        )
+        .is_ok());
+
+    e.skip_time(61);
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(130000), None),
+        d(100, 18),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(200, 18),
+        12,
+    );
+}
+
+#[test]
+fn test_twap_collateral_price() {
+    let (e, tokens, users) = basic_setup();
+
+    let mut config = e.get_config();
+    config.twap_duration_sec = 600;
+    e.update_config(config).assert_success();
+
+    let mut asset_config = e.get_asset(&tokens.wnear).config;
+    asset_config.collateral_price_mode = PriceMode::Conservative;
+    e.update_asset(&tokens.wnear, asset_config).assert_success();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(100, 18),
+    )
+    .assert_success();
+
+    e.skip_time(600);
+
+    // The spot price of NEAR doubles, but the collateral is valued at the TWAP of $10.
+    // 100 NEAR * $10 * 60% = $600 of borrowing power.
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.ndai,
+            price_data(&tokens, Some(200000), None),
+            d(600, 18),
+        )
+        .is_ok());
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(200000), None),
+        d(300, 18),
+    )
+    .assert_success();
+}
+
+#[test]
+fn test_per_asset_price_staleness() {
+    let (e, tokens, users) = basic_setup();
+
+    for token in [&tokens.ndai, &tokens.nusdc] {
+        let mut asset_config = e.get_asset(token).config;
+        asset_config.max_price_staleness_sec = Some(120);
+        e.update_asset(token, asset_config).assert_success();
+    }
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+    e.supply_to_collateral(&users.bob, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    let alice_price_data = price_data(&tokens, Some(100000), None);
+    let bob_price_data = price_data(&tokens, Some(100000), None);
+    e.skip_time(60);
+
+    // The NEAR price is older than the default maximum staleness of 15 seconds.
+    assert!(!e
+        .borrow(&users.bob, &tokens.nusdc, bob_price_data, d(100, 18))
+        .is_ok());
+    assert!(e.get_account(&users.bob).borrowed.is_empty());
+
+    // The stablecoin prices can be up to 2 minutes old.
+    e.borrow(&users.alice, &tokens.nusdc, alice_price_data, d(100, 18))
+        .assert_success();
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.nusdc.account_id(), d(100, 18))],
+    );
+}
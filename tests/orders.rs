@@ -0,0 +1,178 @@
+mod setup;
+
+use crate::setup::*;
+use common::Price;
+use contract::{Order, OrderId, OrderTrigger};
+use near_sdk::json_types::U128;
+
+fn borrow_setup() -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    // 100 NEAR * $10 * 60% = $600 of borrowing power. Borrowing $500 of DAI.
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(500, 18),
+    )
+    .assert_success();
+
+    (e, tokens, users)
+}
+
+#[test]
+fn test_stop_loss_order() {
+    let (e, tokens, users) = borrow_setup();
+
+    // Deleverage 10 NEAR once the price of NEAR falls below $9.
+    let order = Order {
+        trigger: OrderTrigger::PriceBelow {
+            token_id: tokens.wnear.account_id(),
+            price: Price {
+                multiplier: 90000,
+                decimals: 28,
+            },
+        },
+        token_in: tokens.wnear.account_id(),
+        amount_in: Some(U128(d(10, 24))),
+        token_out: tokens.ndai.account_id(),
+        min_amount_out: None,
+        keeper_tip: U128(d(1, 24)),
+    };
+    let order_id: OrderId = e.place_order(&users.alice, order).unwrap_json();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.orders.len(), 1);
+    assert_eq!(account.orders[0].order_id, order_id);
+
+    assert!(!e
+        .execute_order(
+            &users.bob,
+            &users.alice,
+            order_id,
+            price_data(&tokens, Some(100000), None),
+        )
+        .is_ok());
+
+    // 10 NEAR = $85. With 1% fee it repays 84.15 DAI.
+    e.execute_order(
+        &users.bob,
+        &users.alice,
+        order_id,
+        price_data(&tokens, Some(85000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.orders.is_empty());
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(89, 24))],
+    );
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(41585, 16),
+        18,
+    );
+
+    let keeper = e.get_account(&users.bob);
+    assert_balances(&keeper.supplied, &[av(tokens.wnear.account_id(), d(1, 24))]);
+}
+
+#[test]
+fn test_health_factor_order_closes_position() {
+    let (e, tokens, users) = borrow_setup();
+
+    let order = Order {
+        trigger: OrderTrigger::HealthFactorBelow(10500),
+        token_in: tokens.wnear.account_id(),
+        amount_in: None,
+        token_out: tokens.ndai.account_id(),
+        min_amount_out: None,
+        keeper_tip: U128(0),
+    };
+    let order_id: OrderId = e.place_order(&users.alice, order).unwrap_json();
+
+    // 100 NEAR * $9 * 60% = $540 of adjusted collateral for $526 of adjusted debt.
+    // 100 NEAR = $900. With 1% fee it's 891 DAI to repay 500 DAI.
+    e.execute_order(
+        &users.bob,
+        &users.alice,
+        order_id,
+        price_data(&tokens, Some(90000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.orders.is_empty());
+    assert!(account.collateral.is_empty());
+    assert!(account.borrowed.is_empty());
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        d(391, 18),
+        18,
+    );
+}
+
+#[test]
+fn test_keeper_tip_cant_make_account_unhealthy() {
+    let (e, tokens, users) = borrow_setup();
+
+    let order = Order {
+        trigger: OrderTrigger::HealthFactorBelow(10500),
+        token_in: tokens.wnear.account_id(),
+        amount_in: Some(U128(d(1, 24))),
+        token_out: tokens.ndai.account_id(),
+        min_amount_out: None,
+        keeper_tip: U128(d(90, 24)),
+    };
+    let order_id: OrderId = e.place_order(&users.alice, order).unwrap_json();
+
+    // The tip of 90 NEAR leaves 9 NEAR of collateral after the order for about 491 DAI of debt.
+    assert!(!e
+        .execute_order(
+            &users.bob,
+            &users.alice,
+            order_id,
+            price_data(&tokens, Some(90000), None),
+        )
+        .is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.orders.len(), 1);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(100, 24))],
+    );
+    assert!(e.get_account(&users.bob).supplied.is_empty());
+}
+
+#[test]
+fn test_cancel_order() {
+    let (e, tokens, users) = borrow_setup();
+
+    let order = Order {
+        trigger: OrderTrigger::HealthFactorBelow(10500),
+        token_in: tokens.wnear.account_id(),
+        amount_in: None,
+        token_out: tokens.ndai.account_id(),
+        min_amount_out: None,
+        keeper_tip: U128(0),
+    };
+    let order_id: OrderId = e.place_order(&users.alice, order).unwrap_json();
+
+    e.cancel_order(&users.alice, order_id).assert_success();
+    assert!(e.get_account(&users.alice).orders.is_empty());
+
+    assert!(!e
+        .execute_order(
+            &users.bob,
+            &users.alice,
+            order_id,
+            price_data(&tokens, Some(90000), None),
+        )
+        .is_ok());
+}
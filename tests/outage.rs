@@ -0,0 +1,122 @@
+mod setup;
+
+use crate::setup::*;
+
+fn outage_setup() -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, Some(100000), None),
+        d(100, 18),
+    )
+    .assert_success();
+
+    (e, tokens, users)
+}
+
+#[test]
+fn test_decrease_collateral_with_missing_collateral_price() {
+    let (e, tokens, users) = outage_setup();
+
+    // The NEAR price is missing, but the DAI collateral covers the borrowed USDC.
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, None, None),
+        PriceReceiverMsg::Execute {
+            actions: vec![Action::DecreaseCollateral(asset_amount(
+                &tokens.wnear,
+                d(10, 24),
+            ))],
+        },
+    )
+    .assert_success();
+
+    // Without the NEAR price, the remaining collateral is valued at zero.
+    assert!(!e
+        .oracle_call(
+            &users.alice,
+            price_data(&tokens, None, None),
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::DecreaseCollateral(asset_amount(
+                    &tokens.ndai,
+                    d(1000, 18),
+                ))],
+            },
+        )
+        .is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[
+            av(tokens.ndai.account_id(), d(1000, 18)),
+            av(tokens.wnear.account_id(), d(90, 24)),
+        ],
+    );
+}
+
+#[test]
+fn test_repay_without_prices() {
+    let (e, tokens, users) = outage_setup();
+
+    e.execute(
+        &users.alice,
+        vec![Action::Repay(asset_amount(&tokens.nusdc, d(50, 18)))],
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.nusdc.account_id()).balance,
+        d(50, 18),
+        12,
+    );
+
+    // Decreasing the collateral requires the prices.
+    assert!(!e
+        .execute(
+            &users.alice,
+            vec![Action::DecreaseCollateral(asset_amount(
+                &tokens.wnear,
+                d(10, 24),
+            ))],
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_update_asset_can_borrow() {
+    let (e, tokens, users) = outage_setup();
+
+    assert!(!e
+        .update_asset_can_borrow(&users.alice, &tokens.nusdc, false)
+        .is_ok());
+    e.update_asset_can_borrow(&e.owner, &tokens.nusdc, false)
+        .assert_success();
+    assert!(!e.get_asset(&tokens.nusdc).config.can_borrow);
+
+    assert!(!e
+        .borrow(
+            &users.alice,
+            &tokens.nusdc,
+            price_data(&tokens, Some(100000), None),
+            d(100, 18),
+        )
+        .is_ok());
+
+    e.update_asset_can_borrow(&e.owner, &tokens.nusdc, true)
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, Some(100000), None),
+        d(100, 18),
+    )
+    .assert_success();
+}
@@ -0,0 +1,102 @@
+mod setup;
+
+use crate::setup::*;
+use common::Price;
+use near_sdk::{AccountId, Balance};
+
+fn price_feed_setup() -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    (e, tokens, users)
+}
+
+fn borrow_msg(tokens: &Tokens, amount: Balance) -> PriceReceiverMsg {
+    PriceReceiverMsg::Execute {
+        actions: vec![Action::Borrow(asset_amount(&tokens.ndai, amount))],
+    }
+}
+
+#[test]
+fn test_price_feed_borrow() {
+    let (e, tokens, users) = price_feed_setup();
+
+    e.set_feed_prices(price_data(&tokens, Some(100000), None));
+
+    // 100 NEAR * $10 * 60% = $600 of borrowing power.
+    e.execute_with_price_feed(&users.alice, borrow_msg(&tokens, d(500, 18)))
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.ndai.account_id(), d(500, 18))],
+    );
+}
+
+#[test]
+fn test_price_feed_rejects_wide_confidence() {
+    let (e, tokens, users) = price_feed_setup();
+
+    e.set_feed_prices(price_data(&tokens, None, None));
+    let near_price = Price {
+        multiplier: 100000,
+        decimals: 28,
+    };
+
+    // The confidence interval of 2% is wider than the maximum of 1%, so the NEAR price is missing.
+    e.set_feed_price(tokens.wnear.account_id().to_string(), near_price, 2000)
+        .assert_success();
+    assert!(!e
+        .execute_with_price_feed(&users.alice, borrow_msg(&tokens, d(500, 18)))
+        .is_ok());
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+
+    e.set_feed_price(tokens.wnear.account_id().to_string(), near_price, 1000)
+        .assert_success();
+    e.execute_with_price_feed(&users.alice, borrow_msg(&tokens, d(500, 18)))
+        .assert_success();
+    assert_eq!(e.get_account(&users.alice).borrowed.len(), 1);
+}
+
+#[test]
+fn test_price_feed_rejects_stale_prices() {
+    let (e, tokens, users) = price_feed_setup();
+
+    e.set_feed_prices(price_data(&tokens, Some(100000), None));
+    e.skip_time(60);
+
+    assert!(!e
+        .execute_with_price_feed(&users.alice, borrow_msg(&tokens, d(500, 18)))
+        .is_ok());
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+}
+
+#[test]
+fn test_price_feed_aggregated_with_oracles() {
+    let (e, tokens, users) = price_feed_setup();
+
+    let oracle2 = e.deploy_oracle("oracle2.near");
+    let mut config = e.get_config();
+    config.secondary_oracle_account_ids =
+        vec![AccountId::new_unchecked("oracle2.near".to_string())];
+    e.update_config(config).assert_success();
+
+    e.set_feed_prices(price_data(&tokens, Some(100000), None));
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(200000), None))
+        .assert_success();
+
+    // The price feed price of $10 deviates too much from the median with the oracle price of $20.
+    assert!(!e
+        .execute_with_price_feed(&users.alice, borrow_msg(&tokens, d(500, 18)))
+        .is_ok());
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+
+    e.oracle_commit(&oracle2, price_data(&tokens, Some(101000), None))
+        .assert_success();
+    e.execute_with_price_feed(&users.alice, borrow_msg(&tokens, d(500, 18)))
+        .assert_success();
+    assert_eq!(e.get_account(&users.alice).borrowed.len(), 1);
+}
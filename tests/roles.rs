@@ -0,0 +1,129 @@
+mod setup;
+
+use crate::setup::*;
+use common::ONE_YOCTO;
+use contract::Role;
+
+#[test]
+fn test_roles_grant_revoke() {
+    let (e, _tokens, users) = basic_setup();
+
+    // The owner is granted all roles explicitly.
+    assert_eq!(
+        e.get_account_roles(&e.owner),
+        vec![
+            Role::Admin,
+            Role::RiskManager,
+            Role::FarmManager,
+            Role::Guardian,
+            Role::Upgrader
+        ]
+    );
+    assert!(e.get_account_roles(&users.alice).is_empty());
+
+    // Only the admin can grant roles.
+    assert!(!e
+        .grant_role(&users.alice, &users.alice, Role::Admin)
+        .is_ok());
+
+    e.grant_role(&e.owner, &users.alice, Role::Admin)
+        .assert_success();
+    assert!(!e.grant_role(&e.owner, &users.alice, Role::Admin).is_ok());
+
+    // The admin can grant roles to other accounts.
+    e.grant_role(&users.alice, &users.bob, Role::Guardian)
+        .assert_success();
+    e.grant_role(&users.alice, &users.bob, Role::FarmManager)
+        .assert_success();
+    assert_eq!(
+        e.get_account_roles(&users.bob),
+        vec![Role::FarmManager, Role::Guardian]
+    );
+
+    e.revoke_role(&users.alice, &users.bob, Role::FarmManager)
+        .assert_success();
+    assert!(!e
+        .revoke_role(&users.alice, &users.bob, Role::FarmManager)
+        .is_ok());
+    assert_eq!(e.get_account_roles(&users.bob), vec![Role::Guardian]);
+}
+
+#[test]
+fn test_roles_last_admin() {
+    let (e, _tokens, users) = basic_setup();
+
+    // The last admin can't be revoked.
+    assert!(!e.revoke_role(&e.owner, &e.owner, Role::Admin).is_ok());
+
+    e.grant_role(&e.owner, &users.alice, Role::Admin)
+        .assert_success();
+    e.revoke_role(&users.alice, &e.owner, Role::Admin)
+        .assert_success();
+    assert!(!e.get_account_roles(&e.owner).contains(&Role::Admin));
+
+    assert!(!e
+        .revoke_role(&users.alice, &users.alice, Role::Admin)
+        .is_ok());
+    assert_eq!(e.get_account_roles(&users.alice), vec![Role::Admin]);
+}
+
+#[test]
+fn test_roles_guardian_pause() {
+    let (e, tokens, users) = basic_setup();
+
+    assert!(!e
+        .update_asset_can_borrow(&users.alice, &tokens.ndai, false)
+        .is_ok());
+
+    e.grant_role(&e.owner, &users.alice, Role::Guardian)
+        .assert_success();
+
+    e.update_asset_can_borrow(&users.alice, &tokens.ndai, false)
+        .assert_success();
+    assert!(!e.get_asset(&tokens.ndai).config.can_borrow);
+
+    // The guardian can only disable borrowing.
+    assert!(!e
+        .update_asset_can_borrow(&users.alice, &tokens.ndai, true)
+        .is_ok());
+
+    // The guardian can't update the asset config.
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow = true;
+    assert!(!users
+        .alice
+        .function_call(
+            e.contract
+                .contract
+                .update_asset(tokens.ndai.account_id(), asset_config.clone()),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+        .is_ok());
+
+    e.grant_role(&e.owner, &users.bob, Role::RiskManager)
+        .assert_success();
+    users
+        .bob
+        .function_call(
+            e.contract
+                .contract
+                .update_asset(tokens.ndai.account_id(), asset_config),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+        .assert_success();
+
+    // The asset update doesn't undo the pause.
+    assert!(!e.get_asset(&tokens.ndai).config.can_borrow);
+
+    e.update_asset_can_borrow(&users.bob, &tokens.ndai, true)
+        .assert_success();
+    assert!(e.get_asset(&tokens.ndai).config.can_borrow);
+
+    e.revoke_role(&e.owner, &users.alice, Role::Guardian)
+        .assert_success();
+    assert!(!e
+        .update_asset_can_borrow(&users.alice, &tokens.ndai, false)
+        .is_ok());
+}
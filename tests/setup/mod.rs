@@ -15,16 +15,23 @@ pub use contract::{
     AccountDetailedView, Action, AssetAmount, AssetConfig, AssetDetailedView, Config,
     ContractContract as BurrowlandContract, PriceReceiverMsg, TokenReceiverMsg,
 };
-use contract::{AssetFarmView, AssetView, FarmId};
+use contract::{
+    AssetFarmView, AssetView, BoostCurve, FarmId, FarmRewardSchedule, Order, OrderId, PriceMode,
+    ProposalAction, ProposalId, ProposalView, Role, TimelockActionId, TimelockedActionView,
+};
 use near_sdk_sim::runtime::RuntimeStandalone;
+use test_dex::ContractContract as DexContract;
 use test_oracle::ContractContract as OracleContract;
+use test_price_feed::ContractContract as PriceFeedContract;
 
 near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
     BURROWLAND_WASM_BYTES => "res/burrowland.wasm",
     BURROWLAND_0_3_0_WASM_BYTES => "res/burrowland_0.3.0.wasm",
     BURROWLAND_0_4_0_WASM_BYTES => "res/burrowland_0.4.0.wasm",
-    BURROWLAND_PREVIOUS_WASM_BYTES => "res/burrowland_0.6.0.wasm",
+    BURROWLAND_PREVIOUS_WASM_BYTES => "res/burrowland_0.7.0.wasm",
     TEST_ORACLE_WASM_BYTES => "res/test_oracle.wasm",
+    TEST_DEX_WASM_BYTES => "res/test_dex.wasm",
+    TEST_PRICE_FEED_WASM_BYTES => "res/test_price_feed.wasm",
 
     FUNGIBLE_TOKEN_WASM_BYTES => "res/fungible_token.wasm",
 }
@@ -47,6 +54,8 @@ pub fn burrowland_wasm_bytes() -> &'static [u8] {
 
 pub const NEAR: &str = "near";
 pub const ORACLE_ID: &str = "oracle.near";
+pub const DEX_ID: &str = "dex.near";
+pub const PRICE_FEED_ID: &str = "price_feed.near";
 pub const BURROWLAND_ID: &str = "burrowland.near";
 pub const BOOSTER_TOKEN_ID: &str = "token.burrowland.near";
 pub const OWNER_ID: &str = "owner.near";
@@ -70,6 +79,8 @@ pub struct Env {
     pub near: UserAccount,
     pub owner: UserAccount,
     pub oracle: ContractAccount<OracleContract>,
+    pub dex: ContractAccount<DexContract>,
+    pub price_feed: ContractAccount<PriceFeedContract>,
     pub contract: ContractAccount<BurrowlandContract>,
     pub booster_token: UserAccount,
 }
@@ -146,6 +157,22 @@ impl Env {
             deposit: to_yocto("10")
         );
 
+        let dex = deploy!(
+            contract: DexContract,
+            contract_id: DEX_ID.to_string(),
+            bytes: &TEST_DEX_WASM_BYTES,
+            signer_account: near,
+            deposit: to_yocto("10")
+        );
+
+        let price_feed = deploy!(
+            contract: PriceFeedContract,
+            contract_id: PRICE_FEED_ID.to_string(),
+            bytes: &TEST_PRICE_FEED_WASM_BYTES,
+            signer_account: near,
+            deposit: to_yocto("10")
+        );
+
         let contract = deploy!(
             contract: BurrowlandContract,
             contract_id: BURROWLAND_ID.to_string(),
@@ -166,6 +193,20 @@ impl Env {
                     maximum_staking_duration_sec: 31536000,
                     x_booster_multiplier_at_maximum_staking_duration: 40000,
                     force_closing_enabled: true,
+                    dex_account_id: Some(a(DEX_ID)),
+                    reserve_deleverage_fee: Some(100),
+                    secondary_oracle_account_ids: vec![],
+                    max_oracle_price_deviation: 500,
+                    min_oracle_sources: 1,
+                    max_price_change: 2000,
+                    price_change_window_sec: 0,
+                    twap_duration_sec: 1800,
+                    price_feed_account_id: Some(a(PRICE_FEED_ID)),
+                    max_price_feed_confidence: 100,
+                    booster_decay_enabled: false,
+                    booster_early_unstake_penalty: Some(5000),
+                    governance: None,
+                    owner_action_delay_sec: 0,
                 }
             )
         );
@@ -201,6 +242,8 @@ impl Env {
             owner,
             contract,
             oracle,
+            dex,
+            price_feed,
             booster_token,
         }
     }
@@ -247,6 +290,10 @@ impl Env {
                         can_use_as_collateral: false,
                         can_borrow: false,
                         net_tvl_multiplier: 10000,
+                        collateral_price_mode: PriceMode::Spot,
+                        debt_price_mode: PriceMode::Spot,
+                        derived_price: None,
+                        max_price_staleness_sec: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -270,6 +317,10 @@ impl Env {
                         can_use_as_collateral: true,
                         can_borrow: true,
                         net_tvl_multiplier: 10000,
+                        collateral_price_mode: PriceMode::Spot,
+                        debt_price_mode: PriceMode::Spot,
+                        derived_price: None,
+                        max_price_staleness_sec: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -293,6 +344,10 @@ impl Env {
                         can_use_as_collateral: true,
                         can_borrow: true,
                         net_tvl_multiplier: 10000,
+                        collateral_price_mode: PriceMode::Spot,
+                        debt_price_mode: PriceMode::Spot,
+                        derived_price: None,
+                        max_price_staleness_sec: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -316,6 +371,10 @@ impl Env {
                         can_use_as_collateral: true,
                         can_borrow: true,
                         net_tvl_multiplier: 10000,
+                        collateral_price_mode: PriceMode::Spot,
+                        debt_price_mode: PriceMode::Spot,
+                        derived_price: None,
+                        max_price_staleness_sec: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -339,6 +398,10 @@ impl Env {
                         can_use_as_collateral: true,
                         can_borrow: true,
                         net_tvl_multiplier: 10000,
+                        collateral_price_mode: PriceMode::Spot,
+                        debt_price_mode: PriceMode::Spot,
+                        derived_price: None,
+                        max_price_staleness_sec: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -362,6 +425,10 @@ impl Env {
                         can_use_as_collateral: true,
                         can_borrow: true,
                         net_tvl_multiplier: 10000,
+                        collateral_price_mode: PriceMode::Spot,
+                        debt_price_mode: PriceMode::Spot,
+                        derived_price: None,
+                        max_price_staleness_sec: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -428,6 +495,20 @@ impl Env {
             .assert_success();
     }
 
+    pub fn ft_balance_of(&self, token: &UserAccount, user: &UserAccount) -> Balance {
+        let balance: U128 = self
+            .near
+            .view(
+                token.account_id(),
+                "ft_balance_of",
+                &json!({ "account_id": user.account_id() })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        balance.0
+    }
+
     pub fn mint_tokens(&self, tokens: &Tokens, user: &UserAccount) {
         ft_storage_deposit(user, &tokens.wnear.account_id(), &user.account_id());
         ft_storage_deposit(user, &tokens.neth.account_id(), &user.account_id());
@@ -445,6 +526,112 @@ impl Env {
         self.mint_ft(&self.booster_token, user, d(amount, 18));
     }
 
+    pub fn deploy_oracle(&self, oracle_id: &str) -> ContractAccount<OracleContract> {
+        deploy!(
+            contract: OracleContract,
+            contract_id: oracle_id.to_string(),
+            bytes: &TEST_ORACLE_WASM_BYTES,
+            signer_account: self.near,
+            deposit: to_yocto("10")
+        )
+    }
+
+    pub fn get_config(&self) -> Config {
+        self.near
+            .view_method_call(self.contract.contract.get_config())
+            .unwrap_json()
+    }
+
+    pub fn update_config(&self, config: Config) -> ExecutionResult {
+        self.owner.function_call(
+            self.contract.contract.update_config(config),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn update_asset(&self, token: &UserAccount, asset_config: AssetConfig) -> ExecutionResult {
+        self.owner.function_call(
+            self.contract
+                .contract
+                .update_asset(token.account_id(), asset_config),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn oracle_commit(
+        &self,
+        oracle: &ContractAccount<OracleContract>,
+        price_data: PriceData,
+    ) -> ExecutionResult {
+        self.near.function_call(
+            oracle
+                .contract
+                .oracle_commit(self.contract.user_account.account_id(), price_data),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn set_feed_price(
+        &self,
+        asset_id: String,
+        price: Price,
+        confidence: Balance,
+    ) -> ExecutionResult {
+        self.near.function_call(
+            self.price_feed
+                .contract
+                .set_price(asset_id, price, U128(confidence)),
+            DEFAULT_GAS.0,
+            0,
+        )
+    }
+
+    /// Publishes all prices from the price data to the price feed without the confidence interval.
+    pub fn set_feed_prices(&self, price_data: PriceData) {
+        for AssetOptionalPrice { asset_id, price } in price_data.prices {
+            if let Some(price) = price {
+                self.set_feed_price(asset_id, price, 0).assert_success();
+            }
+        }
+    }
+
+    pub fn set_exchange_rate(&self, exchange_rate: Balance) -> ExecutionResult {
+        self.near.function_call(
+            self.price_feed
+                .contract
+                .set_exchange_rate(U128(exchange_rate)),
+            DEFAULT_GAS.0,
+            0,
+        )
+    }
+
+    pub fn update_exchange_rate(&self, token: &UserAccount) -> ExecutionResult {
+        self.near.function_call(
+            self.contract
+                .contract
+                .update_exchange_rate(token.account_id()),
+            MAX_GAS.0,
+            0,
+        )
+    }
+
+    pub fn execute_with_price_feed(
+        &self,
+        user: &UserAccount,
+        msg: PriceReceiverMsg,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .execute_with_price_feed(serde_json::to_string(&msg).unwrap()),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
     pub fn get_asset(&self, token: &UserAccount) -> AssetDetailedView {
         let asset: Option<AssetDetailedView> = self
             .near
@@ -527,6 +714,91 @@ impl Env {
         )
     }
 
+    pub fn execute(&self, user: &UserAccount, actions: Vec<Action>) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.execute(actions),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn execute_timelock_action(
+        &self,
+        user: &UserAccount,
+        action_id: TimelockActionId,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.execute_timelock_action(action_id),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn cancel_timelock_action(
+        &self,
+        user: &UserAccount,
+        action_id: TimelockActionId,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.cancel_timelock_action(action_id),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn get_timelock_actions(&self) -> Vec<TimelockedActionView> {
+        self.near
+            .view_method_call(self.contract.contract.get_timelock_actions())
+            .unwrap_json()
+    }
+
+    pub fn grant_role(
+        &self,
+        caller: &UserAccount,
+        user: &UserAccount,
+        role: Role,
+    ) -> ExecutionResult {
+        caller.function_call(
+            self.contract.contract.grant_role(user.account_id(), role),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn revoke_role(
+        &self,
+        caller: &UserAccount,
+        user: &UserAccount,
+        role: Role,
+    ) -> ExecutionResult {
+        caller.function_call(
+            self.contract.contract.revoke_role(user.account_id(), role),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn get_account_roles(&self, user: &UserAccount) -> Vec<Role> {
+        self.near
+            .view_method_call(self.contract.contract.get_account_roles(user.account_id()))
+            .unwrap_json()
+    }
+
+    pub fn update_asset_can_borrow(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        can_borrow: bool,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .update_asset_can_borrow(token.account_id(), can_borrow),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
     pub fn borrow(
         &self,
         user: &UserAccount,
@@ -600,10 +872,230 @@ impl Env {
         )
     }
 
+    pub fn setup_dex(
+        &self,
+        token_in: &UserAccount,
+        token_out: &UserAccount,
+        liquidity: Balance,
+        numerator: Balance,
+        denominator: Balance,
+    ) {
+        ft_storage_deposit(&self.owner, &token_in.account_id(), &self.dex.account_id());
+        ft_storage_deposit(&self.owner, &token_out.account_id(), &self.dex.account_id());
+        self.mint_ft(token_out, &self.dex.user_account, liquidity);
+        self.owner
+            .function_call(
+                self.dex.contract.set_rate(
+                    token_in.account_id(),
+                    token_out.account_id(),
+                    U128(numerator),
+                    U128(denominator),
+                ),
+                DEFAULT_GAS.0,
+                0,
+            )
+            .assert_success();
+    }
+
+    pub fn set_dex_delay_outputs(&self, delay_outputs: bool) {
+        self.owner
+            .function_call(
+                self.dex.contract.set_delay_outputs(delay_outputs),
+                DEFAULT_GAS.0,
+                0,
+            )
+            .assert_success();
+    }
+
+    pub fn release_dex_outputs(&self) -> ExecutionResult {
+        self.owner
+            .function_call(self.dex.contract.release_outputs(), MAX_GAS.0, 0)
+    }
+
+    pub fn cancel_pending_swap(
+        &self,
+        user: &UserAccount,
+        account: &UserAccount,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .cancel_pending_swap(account.account_id()),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn swap_collateral(
+        &self,
+        user: &UserAccount,
+        token_in: AssetAmount,
+        token_out: &UserAccount,
+        min_amount_out: Balance,
+        price_data: PriceData,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            &user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::SwapCollateral {
+                    token_in,
+                    token_out: token_out.account_id(),
+                    min_amount_out: U128(min_amount_out),
+                }],
+            },
+        )
+    }
+
+    pub fn swap_debt(
+        &self,
+        user: &UserAccount,
+        token_in: AssetAmount,
+        token_out: &UserAccount,
+        min_amount_out: Balance,
+        price_data: PriceData,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            &user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::SwapDebt {
+                    token_in,
+                    token_out: token_out.account_id(),
+                    min_amount_out: U128(min_amount_out),
+                }],
+            },
+        )
+    }
+
+    pub fn deleverage(
+        &self,
+        user: &UserAccount,
+        token_in: AssetAmount,
+        token_out: &UserAccount,
+        min_amount_out: Option<Balance>,
+        price_data: PriceData,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            &user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::Deleverage {
+                    token_in,
+                    token_out: token_out.account_id(),
+                    min_amount_out: min_amount_out.map(U128),
+                }],
+            },
+        )
+    }
+
+    pub fn place_order(&self, user: &UserAccount, order: Order) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.account_place_order(order),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn cancel_order(&self, user: &UserAccount, order_id: OrderId) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.account_cancel_order(order_id),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn execute_order(
+        &self,
+        keeper: &UserAccount,
+        user: &UserAccount,
+        order_id: OrderId,
+        price_data: PriceData,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            &keeper,
+            price_data,
+            PriceReceiverMsg::ExecuteOrder {
+                account_id: user.account_id(),
+                order_id,
+            },
+        )
+    }
+
+    pub fn set_auto_repay(&self, user: &UserAccount, enabled: bool) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.account_set_auto_repay(enabled),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn auto_repay(
+        &self,
+        caller: &UserAccount,
+        user: &UserAccount,
+        token: &UserAccount,
+    ) -> ExecutionResult {
+        caller.function_call(
+            self.contract
+                .contract
+                .auto_repay(user.account_id(), token.account_id()),
+            DEFAULT_GAS.0,
+            0,
+        )
+    }
+
     pub fn skip_time(&self, seconds: u32) {
         self.near.borrow_runtime_mut().cur_block.block_timestamp += to_nano(seconds);
     }
 
+    pub fn create_proposal(&self, user: &UserAccount, action: ProposalAction) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .create_proposal("Test proposal".to_string(), action),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn vote_proposal(
+        &self,
+        user: &UserAccount,
+        proposal_id: ProposalId,
+        approve: bool,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.vote_proposal(proposal_id, approve),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn execute_proposal(&self, user: &UserAccount, proposal_id: ProposalId) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.execute_proposal(proposal_id),
+            MAX_GAS.0,
+            0,
+        )
+    }
+
+    pub fn cancel_proposal(&self, user: &UserAccount, proposal_id: ProposalId) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.cancel_proposal(proposal_id),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn get_proposal(&self, proposal_id: ProposalId) -> ProposalView {
+        let proposal: Option<ProposalView> = self
+            .near
+            .view_method_call(self.contract.contract.get_proposal(proposal_id))
+            .unwrap_json();
+        proposal.unwrap()
+    }
+
     pub fn account_stake_booster(
         &self,
         user: &UserAccount,
@@ -620,8 +1112,46 @@ impl Env {
     }
 
     pub fn account_unstake_booster(&self, user: &UserAccount) -> ExecutionResult {
+        self.account_unstake_booster_amount(user, None)
+    }
+
+    pub fn account_unstake_booster_amount(
+        &self,
+        user: &UserAccount,
+        amount: Option<Balance>,
+    ) -> ExecutionResult {
         user.function_call(
-            self.contract.contract.account_unstake_booster(),
+            self.contract
+                .contract
+                .account_unstake_booster(amount.map(U128)),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn account_early_unstake_booster(
+        &self,
+        user: &UserAccount,
+        amount: Option<Balance>,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .account_early_unstake_booster(amount.map(U128)),
+            DEFAULT_GAS.0,
+            1,
+        )
+    }
+
+    pub fn account_extend_booster_staking(
+        &self,
+        user: &UserAccount,
+        duration: DurationSec,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .account_extend_booster_staking(duration),
             DEFAULT_GAS.0,
             1,
         )
@@ -634,6 +1164,51 @@ impl Env {
         new_reward_per_day: Balance,
         new_booster_log_base: Balance,
         reward_amount: Balance,
+    ) {
+        self.add_farm_with_schedule(
+            farm_id,
+            reward_token,
+            new_reward_per_day,
+            new_booster_log_base,
+            reward_amount,
+            None,
+        );
+    }
+
+    pub fn add_farm_with_schedule(
+        &self,
+        farm_id: FarmId,
+        reward_token: &UserAccount,
+        new_reward_per_day: Balance,
+        new_booster_log_base: Balance,
+        reward_amount: Balance,
+        schedule: Option<FarmRewardSchedule>,
+    ) {
+        self.owner
+            .function_call(
+                self.contract.contract.add_asset_farm_reward(
+                    farm_id,
+                    reward_token.account_id(),
+                    U128::from(new_reward_per_day),
+                    U128::from(new_booster_log_base),
+                    U128::from(reward_amount),
+                    schedule,
+                    None,
+                ),
+                DEFAULT_GAS.0,
+                1,
+            )
+            .assert_success();
+    }
+
+    pub fn add_farm_with_boost_curve(
+        &self,
+        farm_id: FarmId,
+        reward_token: &UserAccount,
+        new_reward_per_day: Balance,
+        new_booster_log_base: Balance,
+        reward_amount: Balance,
+        boost_curve: BoostCurve,
     ) {
         self.owner
             .function_call(
@@ -643,6 +1218,8 @@ impl Env {
                     U128::from(new_reward_per_day),
                     U128::from(new_booster_log_base),
                     U128::from(reward_amount),
+                    None,
+                    Some(boost_curve),
                 ),
                 DEFAULT_GAS.0,
                 1,
@@ -650,13 +1227,66 @@ impl Env {
             .assert_success();
     }
 
+    pub fn update_farm_boost_curve(
+        &self,
+        farm_id: FarmId,
+        reward_token: &UserAccount,
+        boost_curve: BoostCurve,
+    ) -> ExecutionResult {
+        self.owner.function_call(
+            self.contract.contract.update_asset_farm_reward_boost_curve(
+                farm_id,
+                reward_token.account_id(),
+                boost_curve,
+            ),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn cancel_farm_reward(
+        &self,
+        farm_id: FarmId,
+        reward_token: &UserAccount,
+        amount: Option<Balance>,
+    ) -> ExecutionResult {
+        self.owner.function_call(
+            self.contract.contract.cancel_asset_farm_reward(
+                farm_id,
+                reward_token.account_id(),
+                amount.map(U128),
+            ),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
     pub fn account_farm_claim_all(&self, user: &UserAccount) -> ExecutionResult {
         user.function_call(
-            self.contract.contract.account_farm_claim_all(None),
+            self.contract.contract.account_farm_claim_all(None, None),
+            MAX_GAS.0,
+            0,
+        )
+    }
+
+    pub fn account_farm_claim_all_to_wallet(&self, user: &UserAccount) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .account_farm_claim_all(None, Some(true)),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn account_farm_poke(&self, caller: &UserAccount, user: &UserAccount) -> ExecutionResult {
+        caller.function_call(
+            self.contract.contract.account_farm_poke(user.account_id()),
             MAX_GAS.0,
             0,
         )
     }
+
     pub fn account_farm_claim_all_on_behalf(
         &self,
         caller: &UserAccount,
@@ -665,7 +1295,7 @@ impl Env {
         caller.function_call(
             self.contract
                 .contract
-                .account_farm_claim_all(Some(user.account_id())),
+                .account_farm_claim_all(Some(user.account_id()), None),
             MAX_GAS.0,
             0,
         )
@@ -813,7 +1443,6 @@ pub fn basic_setup_with_contract(contract_bytes: &[u8]) -> (Env, Tokens, Users)
         d(1, 23),
     );
 
-
     (e, tokens, users)
 }
 
@@ -876,4 +1505,4 @@ pub fn almost_eq(a: u128, b: u128, prec: u32) {
         "{}",
         format!("Expected {} to eq {}, with precision {}", a, b, prec)
     );
-}
\ No newline at end of file
+}
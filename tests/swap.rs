@@ -0,0 +1,250 @@
+mod setup;
+
+use crate::setup::*;
+
+#[test]
+fn test_swap_collateral() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    // 1 NEAR = 10 DAI
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+
+    let swap_amount = d(10, 24);
+    e.swap_collateral(
+        &users.alice,
+        asset_amount(&tokens.wnear, swap_amount),
+        &tokens.ndai,
+        d(99, 18),
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert_balances(
+        &account.collateral,
+        &[
+            av(tokens.wnear.account_id(), supply_amount - swap_amount),
+            av(tokens.ndai.account_id(), d(100, 18)),
+        ],
+    );
+}
+
+#[test]
+fn test_swap_collateral_below_min_amount_out() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+
+    e.swap_collateral(
+        &users.alice,
+        asset_amount(&tokens.wnear, d(10, 24)),
+        &tokens.ndai,
+        d(101, 18),
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), supply_amount)],
+    );
+}
+
+#[test]
+fn test_swap_collateral_health_check() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    // 100 NEAR * $10 * 60% = $600 of borrowing power. Borrowing $500 of DAI.
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        d(500, 18),
+    )
+    .assert_success();
+
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+
+    // The minimum output of 10 DAI for 50 NEAR is not enough to keep the account healthy.
+    assert!(!e
+        .swap_collateral(
+            &users.alice,
+            asset_amount(&tokens.wnear, d(50, 24)),
+            &tokens.ndai,
+            d(10, 18),
+            price_data(&tokens, Some(100000), None),
+        )
+        .is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), supply_amount)],
+    );
+}
+
+#[test]
+fn test_swap_debt() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    // 1 NEAR = 10 DAI
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+
+    let swap_amount = d(10, 24);
+    e.swap_debt(
+        &users.alice,
+        asset_amount(&tokens.wnear, swap_amount),
+        &tokens.ndai,
+        d(99, 18),
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert_eq!(account.borrowed.len(), 2);
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.wnear.account_id()).balance,
+        swap_amount,
+        18,
+    );
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        borrow_amount - d(100, 18),
+        18,
+    );
+}
+
+#[test]
+fn test_swap_debt_without_borrowed_token_out() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+
+    assert!(!e
+        .swap_debt(
+            &users.alice,
+            asset_amount(&tokens.wnear, d(10, 24)),
+            &tokens.ndai,
+            d(99, 18),
+            price_data(&tokens, Some(100000), None),
+        )
+        .is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert!(account.borrowed.is_empty());
+}
+
+#[test]
+fn test_swap_collateral_async_output() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+    e.set_dex_delay_outputs(true);
+
+    let swap_amount = d(10, 24);
+    e.swap_collateral(
+        &users.alice,
+        asset_amount(&tokens.wnear, swap_amount),
+        &tokens.ndai,
+        d(99, 18),
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    // The DEX used the input tokens, so the swap stays pending until the output is received.
+    let account = e.get_account(&users.alice);
+    let pending_swap = account.pending_swap.unwrap();
+    assert!(pending_swap.input_resolved);
+    assert_eq!(pending_swap.amount_in, swap_amount);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), supply_amount - swap_amount)],
+    );
+
+    e.release_dex_outputs().assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+    assert_balances(
+        &account.collateral,
+        &[
+            av(tokens.wnear.account_id(), supply_amount - swap_amount),
+            av(tokens.ndai.account_id(), d(100, 18)),
+        ],
+    );
+}
+
+#[test]
+fn test_swap_cancel_pending_swap() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    e.setup_dex(&tokens.wnear, &tokens.ndai, d(10000, 18), 1, d(1, 5));
+    e.set_dex_delay_outputs(true);
+
+    e.swap_collateral(
+        &users.alice,
+        asset_amount(&tokens.wnear, d(10, 24)),
+        &tokens.ndai,
+        d(99, 18),
+        price_data(&tokens, Some(100000), None),
+    )
+    .assert_success();
+
+    // Only the guardian can cancel the pending swap.
+    assert!(!e.cancel_pending_swap(&users.bob, &users.alice).is_ok());
+    e.cancel_pending_swap(&e.owner, &users.alice)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.pending_swap.is_none());
+
+    // The output that arrives after the cancellation is refunded back to the DEX.
+    e.release_dex_outputs();
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(90, 24))],
+    );
+}
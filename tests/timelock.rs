@@ -0,0 +1,132 @@
+mod setup;
+
+use crate::setup::*;
+use contract::Role;
+
+const DELAY_SEC: u32 = 2 * 24 * 60 * 60;
+
+fn timelock_setup() -> (Env, Tokens, Users) {
+    let (e, tokens, users) = basic_setup();
+
+    // The config is updated immediately, since the timelock is not enabled yet.
+    let mut config = e.get_config();
+    config.owner_action_delay_sec = DELAY_SEC;
+    e.update_config(config).assert_success();
+
+    e.grant_role(&e.owner, &users.charlie, Role::Guardian)
+        .assert_success();
+
+    (e, tokens, users)
+}
+
+#[test]
+fn test_timelock_update_asset() {
+    let (e, tokens, users) = timelock_setup();
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow = false;
+    e.update_asset(&tokens.ndai, asset_config).assert_success();
+
+    // The update is queued.
+    assert!(e.get_asset(&tokens.ndai).config.can_borrow);
+    let timelock_actions = e.get_timelock_actions();
+    assert_eq!(timelock_actions.len(), 1);
+    assert_eq!(timelock_actions[0].action_id, 0);
+    assert_eq!(
+        timelock_actions[0].timelocked_action.eta,
+        GENESIS_TIMESTAMP + sec_to_nano(DELAY_SEC)
+    );
+
+    assert!(!e.execute_timelock_action(&e.owner, 0).is_ok());
+
+    e.skip_time(DELAY_SEC);
+
+    assert!(!e.execute_timelock_action(&users.alice, 0).is_ok());
+    e.execute_timelock_action(&e.owner, 0).assert_success();
+
+    assert!(!e.get_asset(&tokens.ndai).config.can_borrow);
+    assert!(e.get_timelock_actions().is_empty());
+    assert!(!e.execute_timelock_action(&e.owner, 0).is_ok());
+}
+
+#[test]
+fn test_timelock_guardian_cancel() {
+    let (e, tokens, users) = timelock_setup();
+
+    let mut config = e.get_config();
+    config.max_price_change = 1000;
+    e.update_config(config).assert_success();
+    assert_eq!(e.get_config().max_price_change, 2000);
+
+    assert!(!e.cancel_timelock_action(&users.alice, 0).is_ok());
+    e.cancel_timelock_action(&users.charlie, 0).assert_success();
+    assert!(e.get_timelock_actions().is_empty());
+
+    e.skip_time(DELAY_SEC);
+
+    assert!(!e.execute_timelock_action(&e.owner, 0).is_ok());
+    assert_eq!(e.get_config().max_price_change, 2000);
+
+    // Emergency pauses are not timelocked.
+    e.update_asset_can_borrow(&e.owner, &tokens.ndai, false)
+        .assert_success();
+    assert!(!e.get_asset(&tokens.ndai).config.can_borrow);
+
+    // Enabling borrowing is queued.
+    e.update_asset_can_borrow(&e.owner, &tokens.ndai, true)
+        .assert_success();
+    assert!(!e.get_asset(&tokens.ndai).config.can_borrow);
+    let action_id = e.get_timelock_actions()[0].action_id;
+
+    e.skip_time(DELAY_SEC);
+
+    e.execute_timelock_action(&e.owner, action_id)
+        .assert_success();
+    assert!(e.get_asset(&tokens.ndai).config.can_borrow);
+}
+
+#[test]
+fn test_timelock_update_asset_keeps_pause() {
+    let (e, tokens, users) = timelock_setup();
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.reserve_ratio = 1000;
+    e.update_asset(&tokens.ndai, asset_config).assert_success();
+
+    e.update_asset_can_borrow(&users.charlie, &tokens.ndai, false)
+        .assert_success();
+
+    e.skip_time(DELAY_SEC);
+
+    e.execute_timelock_action(&e.owner, 0).assert_success();
+    let asset_config = e.get_asset(&tokens.ndai).config;
+    assert_eq!(asset_config.reserve_ratio, 1000);
+    assert!(!asset_config.can_borrow);
+}
+
+#[test]
+fn test_timelock_roles() {
+    let (e, _tokens, users) = timelock_setup();
+
+    // Revoking the guardian role is queued, so the guardian can still cancel actions.
+    e.revoke_role(&e.owner, &users.charlie, Role::Guardian)
+        .assert_success();
+    assert_eq!(e.get_account_roles(&users.charlie), vec![Role::Guardian]);
+
+    // Granting the admin role is queued and can be cancelled by the guardian.
+    e.grant_role(&e.owner, &users.alice, Role::Admin)
+        .assert_success();
+    assert!(e.get_account_roles(&users.alice).is_empty());
+    e.cancel_timelock_action(&users.charlie, 1).assert_success();
+
+    // Other roles are granted immediately.
+    e.grant_role(&e.owner, &users.alice, Role::FarmManager)
+        .assert_success();
+    assert_eq!(e.get_account_roles(&users.alice), vec![Role::FarmManager]);
+
+    e.skip_time(DELAY_SEC);
+
+    e.execute_timelock_action(&e.owner, 0).assert_success();
+    assert!(e.get_account_roles(&users.charlie).is_empty());
+    assert!(e.get_timelock_actions().is_empty());
+}
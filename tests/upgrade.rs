@@ -3,8 +3,8 @@ mod setup;
 use crate::setup::*;
 use near_sdk::serde_json;
 
-const PREVIOUS_VERSION: &'static str = "0.6.0";
-const LATEST_VERSION: &'static str = "0.7.0";
+const PREVIOUS_VERSION: &'static str = "0.7.0";
+const LATEST_VERSION: &'static str = "0.8.0";
 
 #[test]
 fn test_version() {
@@ -73,7 +73,7 @@ fn test_upgrade_with_private_key() {
     assert_eq!(version, "0.4.0");
 }
 
-/// Note, the following test has logic specific to verify upgrade to 0.7.0 that modifies internal
+/// Note, the following test has logic specific to verify upgrade to 0.8.0 that modifies internal
 /// account storage, so the available storage should increase.
 #[test]
 fn test_upgrade_by_owner() {